@@ -0,0 +1,95 @@
+use std::{env, process::exit};
+
+use reqwest::{Client, Method, StatusCode};
+use serde_json::{json, Value};
+
+const USAGE: &str = "Usage:
+  relayctl [--addr host:port] [--token token] <command>
+
+Commands:
+  connections           list peers that relay is connected to
+  leader                show leader, round and votes for a new leader
+  sync                  show sync state and last block
+  dial <multiaddr>      dial a relay
+  bans                  list banned peers
+  ban <peerid>          ban a peer and close its connections
+  unban <peerid>        remove ban of a peer
+  evict <trx hash>      remove a transaction from mempool
+  snapshot              make zip file of blockchain for syncing relays
+  log [filter]          show or change log filter, like `debug` or `info,relay_node::relay::events=trace`
+
+Address and token default to CENTICHAIN_ADMIN (127.0.0.1:33370) and CENTICHAIN_ADMIN_TOKEN.";
+
+#[tokio::main]
+async fn main() {
+    let mut addr = env::var("CENTICHAIN_ADMIN").unwrap_or("127.0.0.1:33370".to_string());
+    let mut token = env::var("CENTICHAIN_ADMIN_TOKEN").ok();
+    let mut args: Vec<String> = Vec::new();
+
+    let mut given = env::args().skip(1);
+    while let Some(arg) = given.next() {
+        match arg.as_str() {
+            "--addr" => addr = given.next().unwrap_or_else(|| usage()),
+            "--token" => token = Some(given.next().unwrap_or_else(|| usage())),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => args.push(arg),
+        }
+    }
+    let token = match token {
+        Some(token) => token,
+        None => {
+            eprintln!("Admin token is not set, use --token or CENTICHAIN_ADMIN_TOKEN");
+            exit(1)
+        }
+    };
+
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let (method, path, body) = match args.as_slice() {
+        ["connections"] => (Method::GET, "/connections".to_string(), None),
+        ["leader"] => (Method::GET, "/leader".to_string(), None),
+        ["sync"] => (Method::GET, "/sync".to_string(), None),
+        ["dial", multiaddr] => (Method::POST, "/dial".to_string(), Some(json!({ "addr": multiaddr }))),
+        ["bans"] => (Method::GET, "/bans".to_string(), None),
+        ["ban", peerid] => (Method::POST, format!("/bans/{}", peerid), None),
+        ["unban", peerid] => (Method::DELETE, format!("/bans/{}", peerid), None),
+        ["evict", hash] => (Method::DELETE, format!("/mempool/{}", hash), None),
+        ["snapshot"] => (Method::POST, "/snapshot".to_string(), None),
+        ["log"] => (Method::GET, "/log".to_string(), None),
+        ["log", filter] => (Method::PUT, "/log".to_string(), Some(json!({ "filter": filter }))),
+        _ => usage(),
+    };
+
+    let mut request = Client::new()
+        .request(method, format!("http://{}{}", addr, path))
+        .bearer_auth(token);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Relay admin api is not reachable on {}: {}", addr, e);
+            exit(1)
+        }
+    };
+
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    if status == StatusCode::OK {
+        println!("{}", serde_json::to_string_pretty(&body).unwrap());
+    } else {
+        match body.get("error").and_then(|e| e.as_str()) {
+            Some(e) => eprintln!("{}", e),
+            None => eprintln!("Relay answered with {}", status),
+        }
+        exit(1)
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(1)
+}
@@ -0,0 +1,164 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use chrono::{SubsecRound, Utc};
+use libp2p::{identity::Keypair, PeerId};
+use rust_decimal::Decimal;
+use sp_core::{ed25519::Pair, Pair as PairT};
+
+use crate::relay::practical::genesis::{Allocation, GenesisSpec};
+
+//first port of mongodb instances of devnet relays
+const MONGO_PORT: u16 = 28000;
+//port of p2p listener of devnet relays, each relay has its own loopback ip
+const P2P_PORT: u16 = 40000;
+//coins that are allocated to wallet of each devnet relay in genesis
+const ALLOCATION: u64 = 1000;
+
+pub struct Devnet;
+
+impl Devnet {
+    //make a self-contained local network of relays in a directory
+    //each relay gets a wallet, a fixed keypair, its own mongodb and loopback ip
+    //and all of them share a genesis spec and bootstrap from the first relay
+    pub fn init<'a>(relays: usize, dir: &str) -> Result<(), &'a str> {
+        if relays == 0 || relays > 250 {
+            return Err("Number of devnet relays should be between 1 and 250!");
+        }
+        if fs::create_dir_all(dir).is_err() {
+            return Err("Making devnet directory problem-(commands/devnet 30)");
+        }
+        let root = fs::canonicalize(dir).unwrap();
+        let root = root.to_str().unwrap();
+
+        let mut allocations = Vec::new();
+        let mut first_relay = String::new();
+        for i in 0..relays {
+            let relay_dir = format!("{}/relay-{}", root, i);
+            if fs::create_dir_all(format!("{}/mongo", relay_dir)).is_err()
+                || fs::create_dir_all(format!("{}/data", relay_dir)).is_err()
+            {
+                return Err("Making relay directory problem-(commands/devnet 41)");
+            }
+
+            //wallet of relay and its recovery phrase
+            let (pair, phrase, _) = Pair::generate_with_phrase(None);
+            Self::write(&format!("{}/wallet.dat", relay_dir), &pair.public().to_string())?;
+            Self::write(&format!("{}/wallet.phrase", relay_dir), &phrase)?;
+            allocations.push(Allocation {
+                wallet: pair.public(),
+                value: Decimal::from(ALLOCATION),
+            });
+
+            //fixed keypair of relay for having a known peerid to bootstrap from it
+            let keypair = Keypair::generate_ed25519();
+            let peerid = PeerId::from_public_key(&keypair.public());
+            match keypair.to_protobuf_encoding() {
+                Ok(bytes) => {
+                    if fs::write(format!("{}/keypair.key", relay_dir), bytes).is_err() {
+                        return Err("Writing keypair problem-(commands/devnet 58)");
+                    }
+                }
+                Err(_) => return Err("Encoding keypair problem-(commands/devnet 61)"),
+            }
+
+            let ip = format!("127.0.0.{}", i + 1);
+            let listen = format!("/ip4/{}/tcp/{}", ip, P2P_PORT);
+            let mut env = vec![
+                "CENTICHAIN_NETWORK=devnet".to_string(),
+                format!("CENTICHAIN_GENESIS={}/genesis.json", root),
+                format!("CENTICHAIN_WALLET={}/wallet.dat", relay_dir),
+                format!("CENTICHAIN_KEYPAIR={}/keypair.key", relay_dir),
+                format!(
+                    "CENTICHAIN_MONGO=mongodb://127.0.0.1:{}/?directConnection=true",
+                    MONGO_PORT as usize + i
+                ),
+                format!("CENTICHAIN_LISTEN={}", listen),
+                format!("CENTICHAIN_RPC={}:33369", ip),
+                format!("CENTICHAIN_DATA_DIR={}/data", relay_dir),
+            ];
+            //first relay has no bootstrap relays, so it starts the network
+            if i > 0 {
+                env.push(format!("CENTICHAIN_BOOTSTRAP={}", first_relay));
+            }
+            Self::write(&format!("{}/relay.env", relay_dir), &env.join("\n"))?;
+
+            if i == 0 {
+                first_relay = format!("{}/p2p/{}", listen, peerid);
+            }
+        }
+
+        let genesis = GenesisSpec {
+            chain_id: "centichain-devnet".to_string(),
+            date: Utc::now().round_subsecs(0).to_string(),
+            allocations,
+            validators: Vec::new(),
+            upgrade_height: None,
+        };
+        Self::write(
+            &format!("{}/genesis.json", root),
+            &serde_json::to_string_pretty(&genesis).unwrap(),
+        )?;
+
+        Self::write_start_script(relays, root)?;
+        println!(
+            "Devnet with {} relays created in {}\nGenesis hash: {}\nStart it with: {}/start.sh",
+            relays,
+            root,
+            genesis.hash(),
+            root
+        );
+        Ok(())
+    }
+
+    //script that starts mongodb (as a single node replica set for change streams) and relay of each devnet node
+    fn write_start_script<'a>(relays: usize, root: &str) -> Result<(), &'a str> {
+        let relay_bin = std::env::current_exe().unwrap();
+        let mut script = vec![
+            "#!/bin/sh".to_string(),
+            "# Starts mongodb and relay of every devnet node, logs are in each relay directory".to_string(),
+            format!("cd \"{}\" || exit 1", root),
+        ];
+        for i in 0..relays {
+            let port = MONGO_PORT as usize + i;
+            script.push(format!(
+                "mongod --port {} --dbpath relay-{}/mongo --replSet rs{} --bind_ip 127.0.0.1 --fork --logpath relay-{}/mongo.log",
+                port, i, i, i
+            ));
+            script.push(format!(
+                "mongosh --quiet --port {} --eval 'try {{ rs.initiate() }} catch (e) {{}}'",
+                port
+            ));
+            script.push(format!(
+                "(cd relay-{} && set -a && . ./relay.env && set +a && exec \"{}\") > relay-{}/relay.out 2>&1 &",
+                i,
+                relay_bin.display(),
+                i
+            ));
+            script.push("sleep 5".to_string());
+        }
+        script.push("wait".to_string());
+
+        let path = format!("{}/start.sh", root);
+        Self::write(&path, &script.join("\n"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).ok();
+        }
+        Ok(())
+    }
+
+    fn write<'a>(path: &str, content: &str) -> Result<(), &'a str> {
+        match File::create(Path::new(path)) {
+            Ok(mut file) => match writeln!(file, "{}", content) {
+                Ok(_) => Ok(()),
+                Err(_) => Err("Writing devnet file problem-(commands/devnet 150)"),
+            },
+            Err(_) => Err("Making devnet file problem-(commands/devnet 152)"),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+
+use crate::relay::practical::{
+    config::CONFIG,
+    db::Mongodb,
+    earnings::{Earning, EarningsReport},
+};
+
+pub struct Earnings;
+
+impl Earnings {
+    //index blocks that are not indexed yet, then print earnings of relay's wallet per epoch
+    //and write all of its earnings to a csv file if it was given
+    pub async fn report(csv_path: Option<&str>) -> Result<(), String> {
+        let wallet = CONFIG.wallet()?;
+        let db = Mongodb::connect().await?;
+        Earning::index(&db, &wallet).await?;
+        let report = EarningsReport::make(&db, &wallet).await?;
+
+        println!("Earnings of {}", report.wallet);
+        println!("{:>8} {:>12} {:>12} {:>8} {:>24}", "epoch", "first", "last", "blocks", "total");
+        for epoch in &report.epochs {
+            println!(
+                "{:>8} {:>12} {:>12} {:>8} {:>24}",
+                epoch.epoch, epoch.first_block, epoch.last_block, epoch.blocks, epoch.total
+            );
+        }
+        println!("Total: {}", report.total);
+
+        if let Some(path) = csv_path {
+            let earnings = Earning::all(&db, &wallet).await?;
+            if let Err(e) = fs::write(path, Earning::csv(&earnings)) {
+                return Err(format!("Writing csv file problem: {}", e));
+            }
+            println!("{} earnings written to {}", earnings.len(), path);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use std::io;
+
+use sp_core::Pair;
+
+use crate::relay::practical::keystore::Keystore;
+
+pub struct KeystoreCommand;
+
+impl KeystoreCommand {
+    //make a new wallet and save it encrypted in keystore, phrase is printed once for backup
+    pub fn new_wallet(path: &str) -> Result<(), String> {
+        let (pair, phrase) = Keystore::generate();
+        Self::store(&pair, path)?;
+        println!("Wallet: {}", pair.public());
+        println!("Write down this phrase, it is the only way to recover the wallet:\n{}", phrase);
+        Ok(())
+    }
+
+    //import a wallet from a BIP-39 or seed15 phrase that is read from stdin
+    pub fn import(path: &str) -> Result<(), String> {
+        eprintln!("Enter BIP-39 or seed15 phrase:");
+        let mut phrase = String::new();
+        if let Err(e) = io::stdin().read_line(&mut phrase) {
+            return Err(e.to_string());
+        }
+        let pair = Keystore::from_mnemonic(&phrase)?;
+        Self::store(&pair, path)?;
+        println!("Wallet: {}", pair.public());
+        Ok(())
+    }
+
+    //print public key of keystore
+    pub fn show(path: &str) -> Result<(), String> {
+        let keystore = Keystore::load(path)?;
+        println!("{}", keystore.public);
+        Ok(())
+    }
+
+    //unlock keystore to check its passphrase
+    pub fn check(path: &str) -> Result<(), String> {
+        let keystore = Keystore::load(path)?;
+        let pair = keystore.decrypt(&Keystore::passphrase("Keystore passphrase: ")?)?;
+        println!("Keystore of {} unlocked", pair.public());
+        Ok(())
+    }
+
+    fn store(pair: &sp_core::ed25519::Pair, path: &str) -> Result<(), String> {
+        let passphrase = Keystore::passphrase("New passphrase: ")?;
+        if std::env::var("CENTICHAIN_PASSPHRASE").is_err()
+            && Keystore::passphrase("Repeat passphrase: ")? != passphrase
+        {
+            return Err("Passphrases don't match.".to_string());
+        }
+        if passphrase.is_empty() {
+            return Err("Passphrase can not be empty.".to_string());
+        }
+        Keystore::encrypt(pair, &passphrase)?.save(path)?;
+        println!("Keystore saved in {}", path);
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use libp2p::PeerId;
+
+use serde::Deserialize;
+
+use crate::relay::{
+    practical::{
+        block::header::Header,
+        genesis::GENESIS,
+        light::{HeadersResponse, LightClient, MAX_HEADERS},
+    },
+    tools::merkel::MerkelProof,
+};
+
+//response of /proof of a relay
+#[derive(Debug, Deserialize)]
+struct ProofResponse {
+    header: Option<Header>,
+    proof: Option<MerkelProof>,
+    status: String,
+}
+
+pub struct LightSync;
+
+impl LightSync {
+    //follow headers of a relay from its rpc and verify them as a light client
+    //checkpoints are trusted when more than 2/3 of relays (comma separated peerids) attest to them
+    //then verify inclusion of a transaction if its hash was given
+    pub async fn run(rpc: &str, relays: &str, trx_hash: Option<&String>) -> Result<(), String> {
+        let rpc = rpc.trim_end_matches('/');
+        let relays = relays
+            .split(',')
+            .filter(|relay| !relay.trim().is_empty())
+            .map(|relay| {
+                relay
+                    .trim()
+                    .parse::<PeerId>()
+                    .map_err(|_| format!("Relay {} is not a peerid", relay))
+            })
+            .collect::<Result<HashSet<PeerId>, String>>()?;
+        if relays.is_empty() {
+            return Err("Light client needs at least one relay for checkpoints.".to_string());
+        }
+        let client = reqwest::Client::new();
+        //validators of genesis spec are registered validators, headers of others are verified by checkpoints
+        let validators = match GENESIS.as_ref() {
+            Some(spec) => spec
+                .validators
+                .iter()
+                .map(|validator| (validator.peerid, validator.wallet))
+                .collect(),
+            None => HashMap::new(),
+        };
+        let mut light_client = LightClient::new(relays).validators(validators);
+
+        let mut from = 1;
+        loop {
+            let url = format!("{}/headers?from={}&count={}", rpc, from, MAX_HEADERS);
+            let response: HeadersResponse = match client.get(&url).send().await {
+                Ok(response) => response.json().await.map_err(|e| e.to_string())?,
+                Err(e) => return Err(format!("Requesting headers problem: {}", e)),
+            };
+            if !response.status.is_empty() {
+                return Err(response.status);
+            }
+            let count = response.headers.len() as u64;
+            light_client.follow(response.headers)?;
+            if let Some(checkpoint) = response.checkpoint {
+                light_client.finalize(checkpoint)?;
+            }
+            if count < MAX_HEADERS {
+                break;
+            }
+            from += count;
+        }
+
+        match light_client.tip() {
+            Some(tip) => println!("Tip: block {} {}", tip.number, tip.hash),
+            None => println!("Relay doesn't have any blocks"),
+        }
+        if let Some(checkpoint) = light_client.checkpoint() {
+            println!("Finalized: block {} {}", checkpoint.number, checkpoint.hash);
+        }
+
+        if let Some(trx_hash) = trx_hash {
+            let url = format!("{}/proof", rpc);
+            let response: ProofResponse = match client
+                .post(&url)
+                .json(&serde_json::json!({"tx_hash": trx_hash}))
+                .send()
+                .await
+            {
+                Ok(response) => response.json().await.map_err(|e| e.to_string())?,
+                Err(e) => return Err(format!("Requesting proof problem: {}", e)),
+            };
+            match (response.header, response.proof) {
+                (Some(header), Some(proof)) if proof.leaf == *trx_hash => {
+                    if !light_client.is_verified(header.number) {
+                        return Err(format!(
+                            "Block {} is not finalized and its validator is not registered, try again later.",
+                            header.number
+                        ));
+                    }
+                    if light_client.verify_payment(header.number, &proof) {
+                        let state = if light_client.is_final(header.number) {
+                            "final"
+                        } else {
+                            "not final yet"
+                        };
+                        println!("Transaction is in block {} ({})", header.number, state);
+                    } else {
+                        return Err("Merkel proof of transaction is incorrect.".to_string());
+                    }
+                }
+                _ => return Err(format!("Transaction proof not found: {}", response.status)),
+            }
+        }
+        Ok(())
+    }
+}
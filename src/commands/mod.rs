@@ -0,0 +1,140 @@
+use devnet::Devnet;
+use earnings::Earnings;
+use keystore::KeystoreCommand;
+use light::LightSync;
+use reindex::ReindexCommand;
+use state::VerifyState;
+use transaction::TransactionCommand;
+
+use crate::relay::practical::config::CONFIG;
+
+pub mod devnet;
+pub mod earnings;
+pub mod keystore;
+pub mod light;
+pub mod reindex;
+pub mod state;
+pub mod transaction;
+
+const USAGE: &str = "Usage:
+  relay-node                              start the relay
+  relay-node init-devnet [relays] [dir]   make a local devnet (default: 3 relays in ./devnet)
+  relay-node earnings [--csv file]        report fee earnings of relay's wallet per epoch
+  relay-node keystore new [file]          make a new wallet in an encrypted keystore
+  relay-node keystore import [file]       import a BIP-39 or seed15 phrase into a keystore
+  relay-node keystore show [file]         print wallet address of a keystore
+  relay-node keystore check [file]        unlock a keystore to check its passphrase
+                                          (file defaults to CENTICHAIN_KEYSTORE or /etc/wallet.keystore)
+  relay-node trx pay <wallet> <value>     build a signed transaction with wallet of keystore
+  relay-node trx stake <peerid> <value>   build a stake transaction for a validator
+  relay-node trx unbond <peerid> <value>  build an unbond transaction for a validator
+                                          (trx takes an optional coin selection strategy at the end:
+                                          branch-and-bound, largest-first, smallest-first or random)
+  relay-node light-sync <rpc> <relays> [trx hash]
+                                          follow headers of a relay as a light client and verify a transaction
+                                          (relays are comma separated peerids that checkpoints need 2/3 of them)
+  relay-node verify-state                 recompute utxo set commitment to detect local corruption
+  relay-node reindex [--fresh] [--promote]
+                                          re-verify blocks from genesis and rebuild utxos and reciepts
+                                          (promote replaces state of relay with the rebuilt one)";
+
+//run a command that relay started with it instead of starting the relay
+pub async fn run(args: &[String]) {
+    match args[0].as_str() {
+        "init-devnet" => {
+            let relays = match args.get(1) {
+                Some(number) => number.parse().unwrap_or(0),
+                None => 3,
+            };
+            let dir = args.get(2).map(|d| d.as_str()).unwrap_or("./devnet");
+            if let Err(e) = Devnet::init(relays, dir) {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "earnings" => {
+            let csv_path = match (args.get(1).map(|a| a.as_str()), args.get(2)) {
+                (Some("--csv"), Some(path)) => Some(path.as_str()),
+                (None, _) => None,
+                _ => {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(1)
+                }
+            };
+            if let Err(e) = Earnings::report(csv_path).await {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "keystore" => {
+            let path = match args.get(2) {
+                Some(path) => path.clone(),
+                None => keystore_path(),
+            };
+            let result = match args.get(1).map(|a| a.as_str()) {
+                Some("new") => KeystoreCommand::new_wallet(&path),
+                Some("import") => KeystoreCommand::import(&path),
+                Some("show") => KeystoreCommand::show(&path),
+                Some("check") => KeystoreCommand::check(&path),
+                _ => Err(USAGE.to_string()),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "light-sync" => {
+            let result = match (args.get(1), args.get(2)) {
+                (Some(rpc), Some(relays)) => LightSync::run(rpc, relays, args.get(3)).await,
+                _ => Err(USAGE.to_string()),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "verify-state" => {
+            if let Err(e) = VerifyState::run().await {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "reindex" => {
+            let flags = &args[1..];
+            if flags.iter().any(|a| a != "--fresh" && a != "--promote") {
+                eprintln!("{}", USAGE);
+                std::process::exit(1)
+            }
+            let fresh = flags.iter().any(|a| a == "--fresh");
+            let promote = flags.iter().any(|a| a == "--promote");
+            if let Err(e) = ReindexCommand::run(fresh, promote).await {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        "trx" => {
+            let result = match (args.get(1), args.get(2), args.get(3)) {
+                (Some(kind), Some(to), Some(value)) => {
+                    TransactionCommand::build(&keystore_path(), kind, to, value, args.get(4)).await
+                }
+                _ => Err(USAGE.to_string()),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1)
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(1)
+        }
+    }
+}
+
+//keystore of commands is CENTICHAIN_KEYSTORE or /etc/wallet.keystore
+fn keystore_path() -> String {
+    CONFIG
+        .keystore_path
+        .clone()
+        .unwrap_or("/etc/wallet.keystore".to_string())
+}
@@ -0,0 +1,56 @@
+use crate::relay::practical::{
+    db::Mongodb,
+    reindex::{Reindex, REINDEX_DB},
+};
+
+pub struct ReindexCommand;
+
+impl ReindexCommand {
+    //rebuild chain of relay in reindex database and report inconsistencies of imported state
+    //with promote, the rebuilt state replaces the state of relay when all blocks were verified
+    pub async fn run(fresh: bool, promote: bool) -> Result<(), String> {
+        let source = Mongodb::connect().await?;
+        let target = Mongodb::connect_to(REINDEX_DB).await?;
+        let report = Reindex::run(&source, &target, fresh, |number, total| {
+            println!("Reindexed {}/{} blocks", number, total)
+        })
+        .await?;
+
+        println!("Rebuilt {} blocks in {}", report.blocks, REINDEX_DB);
+        if let Some((number, e)) = report.failed {
+            return Err(format!("Block {} is inconsistent: {}", number, e));
+        }
+        if let Some(number) = report.root_mismatch {
+            println!("Utxo root of block {} differs from the imported one", number);
+        }
+        println!(
+            "UTXOs: {}",
+            if report.utxos_match { "match" } else { "differ" }
+        );
+        println!(
+            "Validators: {}",
+            if report.validators_match { "match" } else { "differ" }
+        );
+        println!(
+            "Reciepts: {} missing, {} extra in the imported state",
+            report.missing_reciepts, report.extra_reciepts
+        );
+
+        if promote {
+            let names = Reindex::promote(&source, &target).await?;
+            println!("Promoted {} collections of {} to the relay state", names.len(), REINDEX_DB);
+            return Ok(());
+        }
+
+        if report.utxos_match
+            && report.validators_match
+            && report.root_mismatch.is_none()
+            && report.missing_reciepts == 0
+            && report.extra_reciepts == 0
+        {
+            Ok(())
+        } else {
+            Err("Imported state is inconsistent with its blocks.".to_string())
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::relay::practical::{commitment::UtxoCommitment, db::Mongodb};
+
+pub struct VerifyState;
+
+impl VerifyState {
+    //recompute commitment of utxo set from scratch and compare it with the saved utxo root
+    pub async fn run() -> Result<(), String> {
+        let db = Mongodb::connect().await?;
+        let utxos = UtxoCommitment::chain_utxos(&db).await?;
+        let root = UtxoCommitment::of(&utxos);
+        println!("{} utxos, root {}", utxos.len(), root);
+
+        match UtxoCommitment::last(&db).await? {
+            Some(commitment) if commitment.root == root => {
+                println!("Utxo set matches the root of block {}", commitment.number);
+                Ok(())
+            }
+            Some(commitment) => Err(format!(
+                "Utxo set doesn't match the root of block {}: {}",
+                commitment.number, commitment.root
+            )),
+            None => Err("There is no any saved utxo root.".to_string()),
+        }
+    }
+}
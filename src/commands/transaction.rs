@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use libp2p::PeerId;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    Collection,
+};
+use rust_decimal::Decimal;
+use sp_core::{crypto::Ss58Codec, ed25519::Public, Pair};
+
+use crate::relay::{
+    practical::{
+        builder::TransactionBuilder,
+        coin_selection::{self, Strategy},
+        db::Mongodb,
+        keystore::Keystore,
+    },
+    tools::utxo::Person,
+};
+
+pub struct TransactionCommand;
+
+impl TransactionCommand {
+    //build a signed transaction with wallet of keystore and utxos of local database
+    //transaction is printed as json for sending to a relay
+    //utxos that mempool of local relay spends are not selected
+    pub async fn build(
+        keystore_path: &str,
+        kind: &str,
+        to: &str,
+        value: &str,
+        strategy: Option<&String>,
+    ) -> Result<(), String> {
+        let value = match Decimal::from_str(value) {
+            Ok(value) => value,
+            Err(_) => return Err("Value is not a decimal.".to_string()),
+        };
+        let strategy: Strategy = match strategy {
+            Some(name) => match serde_json::from_value(serde_json::Value::String(name.clone())) {
+                Ok(strategy) => strategy,
+                Err(_) => return Err(format!("Unknown coin selection strategy: {}", name)),
+            },
+            None => Strategy::default(),
+        };
+        let keystore = Keystore::load(keystore_path)?;
+        let pair = keystore.decrypt(&Keystore::passphrase("Keystore passphrase: ")?)?;
+
+        let db = Mongodb::connect().await?;
+        let collection: Collection<Document> = db.collection("UTXOs");
+        let utxos = match collection
+            .find_one(doc! {"wallet": pair.public().to_string()})
+            .await
+        {
+            Ok(Some(doc)) => from_document::<Person>(doc).unwrap().utxos,
+            Ok(None) => Vec::new(),
+            Err(_) => return Err("Querying utxos problem.".to_string()),
+        };
+        let reserved = coin_selection::reserved(&db, &utxos).await?;
+        let utxos = utxos
+            .into_iter()
+            .filter(|utxo| !reserved.contains(&utxo.unspent_hash))
+            .collect();
+
+        let builder = TransactionBuilder::new(pair)
+            .utxos(utxos)
+            .strategy(strategy);
+        let builder = match kind {
+            "pay" => match Public::from_ss58check(to) {
+                Ok(wallet) => builder.pay(wallet, value),
+                Err(_) => return Err("Wallet address is incorrect.".to_string()),
+            },
+            "stake" | "unbond" => match PeerId::from_str(to) {
+                Ok(validator) if kind == "stake" => builder.stake(validator, value),
+                Ok(validator) => builder.unbond(validator, value),
+                Err(_) => return Err("Validator peerid is incorrect.".to_string()),
+            },
+            _ => return Err(format!("Unknown transaction kind: {}", kind)),
+        };
+
+        let trx = builder.build()?;
+        println!("{}", serde_json::to_string_pretty(&trx).unwrap());
+        Ok(())
+    }
+}
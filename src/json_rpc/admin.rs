@@ -0,0 +1,203 @@
+use std::{net::SocketAddr, time::Duration};
+
+use axum::{
+    extract::{Path, Request},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use axum_server::Handle;
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::relay::{
+    practical::{config::CONFIG, db::Mongodb},
+    tools::{
+        admin::{Admin, AdminCommand, Bans},
+        logger::Logger,
+        shutdown::Shutdown,
+        zipp::Zip,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DialReq {
+    pub addr: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogReq {
+    pub filter: String,
+}
+
+//api for operators of relay that listens on a local address and needs the admin token
+pub struct AdminApi;
+
+impl AdminApi {
+    pub async fn serve(token: String) -> Result<(), String> {
+        let app: Router = Router::new()
+            .route("/connections", get(connections))
+            .route("/leader", get(leader))
+            .route("/sync", get(sync))
+            .route("/dial", post(dial))
+            .route("/bans", get(bans))
+            .route("/bans/:peerid", post(ban).delete(unban))
+            .route("/mempool/:hash", delete(evict))
+            .route("/snapshot", post(snapshot))
+            .route("/log", get(log_filter).put(set_log_filter))
+            .layer(middleware::from_fn(move |request, next| {
+                authorize(token.clone(), request, next)
+            }));
+
+        let addr: SocketAddr = match CONFIG.admin_addr.parse() {
+            Ok(addr) => addr,
+            Err(_) => return Err(format!("Admin address {} is not valid", CONFIG.admin_addr)),
+        };
+        if !addr.ip().is_loopback() {
+            warn!("Admin api is listening on {} that is not a local address", addr);
+        }
+        info!("Admin api is listening on {}", addr);
+
+        let handle = Handle::new();
+        let shutdown_handle = handle.clone();
+        let waiter = tokio::spawn(async move {
+            Shutdown::token().cancelled().await;
+            shutdown_handle.graceful_shutdown(Some(Duration::from_secs(10)));
+        });
+
+        let served = axum_server::bind(addr)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await;
+        waiter.abort();
+        served.map_err(|e| format!("error from admin server:\n{}", e))
+    }
+}
+
+//every request must have `Authorization: Bearer <token>`
+async fn authorize(token: String, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| same(given.as_bytes(), token.as_bytes()));
+    if authorized {
+        next.run(request).await
+    } else {
+        failure(StatusCode::UNAUTHORIZED, "Admin token is missing or wrong")
+    }
+}
+
+//compare tokens in a time that doesn't depend on where they differ
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn failure(status: StatusCode, error: &str) -> Response {
+    (status, Json(json!({ "error": error }))).into_response()
+}
+
+fn done(result: Result<(), String>) -> Response {
+    match result {
+        Ok(_) => Json(json!({ "status": "done" })).into_response(),
+        Err(e) => failure(StatusCode::BAD_REQUEST, &e),
+    }
+}
+
+fn answer<T: Serialize>(result: Result<T, String>) -> Response {
+    match result {
+        Ok(answer) => Json(answer).into_response(),
+        Err(e) => failure(StatusCode::SERVICE_UNAVAILABLE, &e),
+    }
+}
+
+async fn connections() -> Response {
+    answer(Admin::ask(AdminCommand::Connections).await)
+}
+
+async fn leader() -> Response {
+    answer(Admin::ask(AdminCommand::Leader).await)
+}
+
+async fn sync() -> Response {
+    answer(Admin::ask(AdminCommand::Sync).await)
+}
+
+async fn dial(Json(req): Json<DialReq>) -> Response {
+    match Admin::ask(|answer| AdminCommand::Dial(req.addr, answer)).await {
+        Ok(dialed) => done(dialed),
+        Err(e) => failure(StatusCode::SERVICE_UNAVAILABLE, &e),
+    }
+}
+
+async fn bans() -> Response {
+    match Mongodb::connect().await {
+        Ok(db) => match Bans::get(&db).await {
+            Ok(peerids) => {
+                let peerids: Vec<String> = peerids.iter().map(|p| p.to_string()).collect();
+                Json(peerids).into_response()
+            }
+            Err(e) => failure(StatusCode::INTERNAL_SERVER_ERROR, e),
+        },
+        Err(_) => failure(StatusCode::SERVICE_UNAVAILABLE, "Database is not available"),
+    }
+}
+
+async fn ban(Path(req): Path<String>) -> Response {
+    match req.parse::<PeerId>() {
+        Ok(peerid) => match Admin::ask(|answer| AdminCommand::Ban(peerid, answer)).await {
+            Ok(banned) => done(banned),
+            Err(e) => failure(StatusCode::SERVICE_UNAVAILABLE, &e),
+        },
+        Err(_) => failure(StatusCode::BAD_REQUEST, "Peerid is not valid"),
+    }
+}
+
+async fn unban(Path(req): Path<String>) -> Response {
+    match req.parse::<PeerId>() {
+        Ok(peerid) => match Admin::ask(|answer| AdminCommand::Unban(peerid, answer)).await {
+            Ok(unbanned) => done(unbanned),
+            Err(e) => failure(StatusCode::SERVICE_UNAVAILABLE, &e),
+        },
+        Err(_) => failure(StatusCode::BAD_REQUEST, "Peerid is not valid"),
+    }
+}
+
+async fn evict(Path(hash): Path<String>) -> Response {
+    match Admin::ask(|answer| AdminCommand::Evict(hash, answer)).await {
+        Ok(Ok(hash)) => Json(json!({ "status": "done", "hash": hash })).into_response(),
+        Ok(Err(e)) if e == "Transaction is not in mempool" => failure(StatusCode::NOT_FOUND, &e),
+        Ok(Err(e)) => failure(StatusCode::INTERNAL_SERVER_ERROR, &e),
+        Err(e) => failure(StatusCode::SERVICE_UNAVAILABLE, &e),
+    }
+}
+
+//make zip file of blockchain that new relays download for syncing
+async fn snapshot() -> Response {
+    info!("Making snapshot of blockchain by admin");
+    match tokio::task::spawn_blocking(|| Zip::maker().map_err(|e| e.to_string())).await {
+        Ok(Ok(_)) => Json(json!({ "status": "done", "path": CONFIG.zip_path() })).into_response(),
+        Ok(Err(e)) => failure(StatusCode::INTERNAL_SERVER_ERROR, &e),
+        Err(_) => failure(StatusCode::INTERNAL_SERVER_ERROR, "Making snapshot stopped"),
+    }
+}
+
+async fn log_filter() -> Response {
+    Json(LogReq {
+        filter: Logger::filter(),
+    })
+    .into_response()
+}
+
+async fn set_log_filter(Json(req): Json<LogReq>) -> Response {
+    let changed = Logger::set_filter(&req.filter);
+    if changed.is_ok() {
+        info!("Log filter changed to {} by admin", req.filter);
+    }
+    done(changed)
+}
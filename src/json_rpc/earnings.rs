@@ -0,0 +1,63 @@
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::relay::practical::{
+    config::CONFIG,
+    db::Mongodb,
+    earnings::{Earning, EarningsReport},
+};
+
+use super::server::EarningsRes;
+
+//report of indexed earnings of this relay's wallet per epoch
+pub async fn handle_earnings() -> Json<EarningsRes> {
+    let wallet = match CONFIG.wallet() {
+        Ok(wallet) => wallet,
+        Err(e) => {
+            return Json(EarningsRes {
+                report: None,
+                status: e,
+            })
+        }
+    };
+
+    match Mongodb::connect().await {
+        Ok(db) => match EarningsReport::make(&db, &wallet).await {
+            Ok(report) => Json(EarningsRes {
+                report: Some(report),
+                status: "".to_string(),
+            }),
+            Err(e) => Json(EarningsRes {
+                report: None,
+                status: e.to_string(),
+            }),
+        },
+        Err(_) => Json(EarningsRes {
+            report: None,
+            status: "Relay has problem! try with anothers.".to_string(),
+        }),
+    }
+}
+
+//all of indexed earnings of this relay's wallet as csv for accounting
+pub async fn handle_earnings_csv() -> impl IntoResponse {
+    let wallet = match CONFIG.wallet() {
+        Ok(wallet) => wallet,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    };
+
+    match Mongodb::connect().await {
+        Ok(db) => match Earning::all(&db, &wallet).await {
+            Ok(earnings) => (
+                [(header::CONTENT_TYPE, "text/csv")],
+                Earning::csv(&earnings),
+            )
+                .into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
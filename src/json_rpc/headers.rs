@@ -0,0 +1,18 @@
+use axum::{extract::Query, Json};
+
+use crate::relay::practical::{
+    db::Mongodb,
+    light::{HeadersRequest, HeadersResponse},
+};
+
+//compact headers of a range of blocks with the last checkpoint for light clients
+pub async fn handle_headers(Query(request): Query<HeadersRequest>) -> Json<HeadersResponse> {
+    match Mongodb::connect().await {
+        Ok(db) => Json(request.handle(&db).await),
+        Err(_) => Json(HeadersResponse {
+            headers: Vec::new(),
+            checkpoint: None,
+            status: "Relay has problem! try with anothers.".to_string(),
+        }),
+    }
+}
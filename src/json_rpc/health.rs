@@ -0,0 +1,13 @@
+use axum::Json;
+
+use crate::relay::tools::{shutdown::Shutdown, supervisor::Supervisor};
+
+use super::server::HealthRes;
+
+//state of supervised tasks of relay
+pub async fn handle_health() -> Json<HealthRes> {
+    Json(HealthRes {
+        tasks: Supervisor::health(),
+        shutting_down: Shutdown::is_requested(),
+    })
+}
@@ -0,0 +1,45 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::relay::{practical::db::Mongodb, tools::metrics::METRICS};
+
+//metrics of relay in prometheus text format
+pub async fn handle_metrics() -> Response {
+    match Mongodb::connect().await {
+        Ok(db) => match METRICS.render(&db).await {
+            Ok(body) => (
+                [(
+                    header::CONTENT_TYPE,
+                    "application/openmetrics-text; version=1.0.0; charset=utf-8",
+                )],
+                body,
+            )
+                .into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Relay has problem! try with anothers.",
+        )
+            .into_response(),
+    }
+}
+
+//measure latency of requests by their route
+//requests that match no route share one label, so random paths can't grow the metric without limit
+pub async fn track_latency(request: Request, next: Next) -> Response {
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => "unmatched".to_string(),
+    };
+    let start = Instant::now();
+    let response = next.run(request).await;
+    METRICS.rpc_latency(&route, start.elapsed().as_secs_f64());
+    response
+}
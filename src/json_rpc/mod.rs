@@ -4,4 +4,12 @@ mod transaction;
 mod utxo;
 mod reciept;
 mod block;
+mod earnings;
+mod proof;
+mod headers;
+mod state;
+mod admin;
+pub use admin::AdminApi;
+mod health;
+mod metrics;
 pub mod one_utxo;
@@ -3,128 +3,104 @@ use std::str::FromStr;
 use axum::{extract, Json};
 use mongodb::{
     bson::{doc, from_document, Document},
-    Collection,
+    Collection, Database,
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::relay::{practical::db::Mongodb, tools::utxo::{Person, UTXO}};
+use crate::relay::{
+    practical::{
+        coin_selection::{self, Strategy},
+        db::Mongodb,
+    },
+    tools::utxo::{Person, UTXO},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReqBody {
     public_key: String,
     request: String,
     value: String,
+    #[serde(default)]
+    strategy: Strategy,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ResBody {
     public_key: String,
     utxo_data: Vec<UTXO>,
+    fee: Option<String>,
+    change: Option<String>,
     status: String,
     description: String,
 }
 
+impl ResBody {
+    fn error(public_key: String, description: &str) -> Json<Self> {
+        Json(Self {
+            public_key,
+            utxo_data: Vec::new(),
+            fee: None,
+            change: None,
+            status: "error".to_string(),
+            description: description.to_string(),
+        })
+    }
+}
+
 pub async fn a_utxo(extract::Json(request): extract::Json<ReqBody>) -> Json<ResBody> {
+    let value = match Decimal::from_str(&request.value) {
+        Ok(value) => value,
+        Err(_) => return ResBody::error(request.public_key, "Value is not a decimal number"),
+    };
     match Mongodb::connect().await {
         Ok(db) => {
             let utxos_coll: Collection<Document> = db.collection("UTXOs");
             let filter = doc! {"wallet": request.public_key.clone()};
             match utxos_coll.find_one(filter).await {
-                Ok(doc) => match doc {
-                    Some(document) => set_response_utxos(document, request),
-                    None => {
-                        let res = ResBody {
-                            public_key: request.public_key,
-                            utxo_data: Vec::new(),
-                            status: "error".to_string(),
-                            description: "There is no any utxo with this public key".to_string(),
-                        };
-                        return Json(res);
-                    }
-                },
-                Err(_) => {
-                    let res = ResBody {
-                        public_key: request.public_key,
-                        utxo_data: Vec::new(),
-                        status: "error".to_string(),
-                        description: "There is no any utxo with this public key".to_string(),
-                    };
-                    return Json(res);
+                Ok(Some(document)) => {
+                    let person: Person = from_document(document).unwrap();
+                    set_response_utxos(&db, person, value, request).await
                 }
+                _ => ResBody::error(
+                    request.public_key,
+                    "There is no any utxo with this public key",
+                ),
             }
         }
-        Err(_) => {
-            let res = ResBody {
-                public_key: request.public_key,
-                utxo_data: Vec::new(),
-                status: "error".to_string(),
-                description: "Provider Problem! please try with another providers.".to_string(),
-            };
-            return Json(res);
-        }
+        Err(_) => ResBody::error(
+            request.public_key,
+            "Provider Problem! please try with another providers.",
+        ),
     }
 }
 
-fn set_response_utxos(document: Document, request: ReqBody) -> Json<ResBody> {
-    let person: Person = from_document(document).unwrap();
-    let value = Decimal::from_str(&request.value).unwrap(); //convert string of requst's value to Decimal
-    let fee = value * Decimal::from_str("0.01").unwrap();
-    let mut all_utxos_data = Vec::new();
-    let mut utxo_data = Vec::new();
-    for utxo in person.utxos {
-        all_utxos_data.push(utxo);
-    }
-    //sort utxo_data by unspent from smallest to largest
-    all_utxos_data.sort_by(|a, b| a.unspent.cmp(&b.unspent));
-    let unspents_sum: Decimal = all_utxos_data
-        .iter()
-        .map(|data| data.unspent.round_dp(12))
-        .sum();
-    //get nearest unespent to value for send to client
-    if unspents_sum >= value + fee {
-        match all_utxos_data
-            .iter()
-            .position(|data| data.unspent >= value + fee)
-        {
-            Some(index) => {
-                utxo_data.push(all_utxos_data[index].clone());
-            }
-            None => {
-                let mut sum_data = vec![all_utxos_data.pop().unwrap()];
-                for i in 0..all_utxos_data.len() {
-                    let sum_data_unspents_sum: Decimal =
-                        sum_data.iter().map(|data| data.unspent).sum();
-
-                    if (sum_data_unspents_sum.round_dp(12) + all_utxos_data[i].unspent.round_dp(12))
-                        >= value + fee
-                    {
-                        for data in sum_data.clone() {
-                            utxo_data.push(data);
-                        }
-                        utxo_data.push(all_utxos_data[i].clone());
-                        break;
-                    } else {
-                        sum_data.push(all_utxos_data[i].clone())
-                    }
-                }
-            }
-        }
+//select utxos of wallet with requested strategy, utxos that mempool spends are not selected
+async fn set_response_utxos(
+    db: &Database,
+    person: Person,
+    value: Decimal,
+    request: ReqBody,
+) -> Json<ResBody> {
+    let reserved = match coin_selection::reserved(db, &person.utxos).await {
+        Ok(reserved) => reserved,
+        Err(e) => return ResBody::error(request.public_key, e),
+    };
+    let utxos: Vec<UTXO> = person
+        .utxos
+        .into_iter()
+        .filter(|utxo| !reserved.contains(&utxo.unspent_hash))
+        .collect();
 
-        let res = ResBody {
+    match request.strategy.select(&utxos, value) {
+        Ok(selection) => Json(ResBody {
             public_key: request.public_key,
-            utxo_data,
+            utxo_data: selection.utxos,
+            fee: Some(selection.fee.to_string()),
+            change: Some(selection.change.to_string()),
             status: "success".to_string(),
             description: "done".to_string(),
-        };
-        return Json(res);
-    } else {
-        let res = ResBody {
-            public_key: request.public_key,
-            utxo_data: Vec::new(),
-            status: "error".to_string(),
-            description: "You don't have enough CENTI".to_string(),
-        };
-        return Json(res);
+        }),
+        Err(e) => ResBody::error(request.public_key, e),
     }
 }
@@ -0,0 +1,42 @@
+use axum::{extract, Json};
+use mongodb::{
+    bson::{doc, from_document, Document},
+    Collection,
+};
+
+use crate::relay::practical::{block::block::Block, db::Mongodb};
+
+use super::server::{ProofRes, TxReq};
+
+//merkel proof of a transaction with header of its block
+//light clients check it with Header::includes against the signed header
+pub async fn handle_proof(extract::Json(tx_req): extract::Json<TxReq>) -> Json<ProofRes> {
+    let mut res = ProofRes {
+        header: None,
+        proof: None,
+        status: String::new(),
+    };
+
+    match Mongodb::connect().await {
+        Ok(db) => {
+            let block_coll: Collection<Document> = db.collection("Blocks");
+            let filter = doc! {"body.transactions.hash": &tx_req.tx_hash};
+            match block_coll.find_one(filter).await {
+                Ok(Some(doc)) => {
+                    let block: Block = from_document(doc).unwrap();
+                    //blocks of older merkel roots don't have proofs
+                    match block.proof(&tx_req.tx_hash) {
+                        Some(proof) if block.header.includes(&proof) => res.proof = Some(proof),
+                        _ => res.status = "Block of transaction doesn't have a merkel proof!".to_string(),
+                    }
+                    res.header = Some(block.header);
+                }
+                Ok(None) => res.status = "Transaction is not in any block!".to_string(),
+                Err(_) => res.status = "Relay has problem! try with anothers.".to_string(),
+            }
+        }
+        Err(_) => res.status = "Relay has problem! try with anothers.".to_string(),
+    }
+
+    Json(res)
+}
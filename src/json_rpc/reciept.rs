@@ -11,8 +11,9 @@ use mongodb::{
     Collection,
 };
 use rust_decimal::Decimal;
+use tracing::error;
 
-use crate::relay::{practical::db::Mongodb, tools::create_log::write_log};
+use crate::relay::practical::db::Mongodb;
 
 use super::server::{RcptReq, RcptRes, Reciept, TxReq};
 
@@ -167,14 +168,14 @@ async fn onchange(mut socket: WebSocket) {
                                                     .await
                                                     .is_err()
                                                 {
-                                                    write_log("Error sending message to client-(RPC-server/ reciept.rs/ 53)");
+                                                    error!("Error sending message to client-(RPC-server/ reciept.rs/ 53)");
                                                     break;
                                                 }
                                             }
                                         }
                                     }
                                 } else {
-                                    write_log(
+                                    error!(
                                 "Failed to create change stream-(RPC-server/ reciept.rs/ 61)",
                             );
                                 }
@@ -190,7 +191,7 @@ async fn onchange(mut socket: WebSocket) {
             });
         }
         Err(_) => {
-            write_log("Error connecting to MongoDB-(RPC-server/ reciept.rs/ 106)");
+            error!("Error connecting to MongoDB-(RPC-server/ reciept.rs/ 106)");
         }
     }
 }
@@ -1,21 +1,36 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 use tower::limit::ConcurrencyLimitLayer;
 
-use axum::{http::Method, routing::{get, post}, Router};
+use axum::{http::Method, middleware, routing::{get, post}, Router};
+use axum_server::Handle;
 use tower_http::{
     cors::{AllowHeaders, Any, CorsLayer},
     services::ServeDir,
 };
 
-use crate::relay::{practical::block::block::Block, tools::create_log::write_log};
+use crate::relay::{
+    practical::{
+        block::{block::Block, header::Header},
+        commitment::UtxoCommitment,
+        config::CONFIG,
+        earnings::EarningsReport,
+    },
+    tools::{merkel::MerkelProof, shutdown::Shutdown, supervisor::TaskHealth},
+};
 
 use super::{
     block::handle_block,
+    earnings::{handle_earnings, handle_earnings_csv},
+    headers::handle_headers,
+    health::handle_health,
+    metrics::{handle_metrics, track_latency},
     one_utxo::a_utxo,
+    proof::handle_proof,
     reciept::{handle_reciept, handle_user_reciepts, ws_reciept},
+    state::handle_state,
     transaction::handle_transaction,
     utxo::{handle_utxo, handle_utxo_ws},
 };
@@ -68,6 +83,31 @@ pub struct BlockRes {
     pub status: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofRes {
+    pub header: Option<Header>,
+    pub proof: Option<MerkelProof>,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StateRes {
+    pub commitment: Option<UtxoCommitment>,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthRes {
+    pub tasks: Vec<TaskHealth>,
+    pub shutting_down: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EarningsRes {
+    pub report: Option<EarningsReport>,
+    pub status: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TxRes {
     pub hash: String,
@@ -78,7 +118,8 @@ pub struct TxRes {
 pub struct Rpc;
 
 impl Rpc {
-    pub async fn handle_requests() {
+    //routes of rpc, latency of every request is measured, even requests that match no route
+    fn router() -> Router {
         let cors = CorsLayer::new()
             .allow_methods([Method::GET, Method::POST])
             .allow_origin(Any)
@@ -92,27 +133,82 @@ impl Rpc {
                 axum::http::HeaderName::from_static("sec-websocket-version"),
             ]);
 
-        let app: Router = Router::new()
+        Router::new()
             .route("/trx", post(handle_transaction))
             .route("/utxo", post(handle_utxo))
             .route("/reciept", post(handle_reciept))
             .route("/urec", post(handle_user_reciepts))
             .route("/block", post(handle_block))
             .route("/autxo", post(a_utxo))
-            .route("/reciept/ws", get(|ws| ws_reciept(ws)))
-            .route("/utxo/ws", get(|ws| handle_utxo_ws(ws)))
+            .route("/proof", post(handle_proof))
+            .route("/headers", get(handle_headers))
+            .route("/state", get(handle_state))
+            .route("/health", get(handle_health))
+            .route("/earnings", get(handle_earnings))
+            .route("/earnings/csv", get(handle_earnings_csv))
+            .route("/reciept/ws", get(ws_reciept))
+            .route("/utxo/ws", get(handle_utxo_ws))
+            .route("/metrics", get(handle_metrics))
+            .layer(middleware::from_fn(track_latency))
             .layer(cors)
             .layer(ws_layer)
             .layer(ConcurrencyLimitLayer::new(100))
-            .nest_service("/blockchain", ServeDir::new("/home"));
+            .nest_service("/blockchain", ServeDir::new(CONFIG.serve_dir()))
+    }
+
+    pub async fn handle_requests() -> Result<(), String> {
+        let app = Self::router();
 
         // let config = RustlsConfig::from_pem_file("/etc/cert.pem", "/etc/key.pem").await.unwrap();
 
-        let addr = SocketAddr::from(([0, 0, 0, 0], 33369));
+        let addr: SocketAddr = match CONFIG.rpc_addr.parse() {
+            Ok(addr) => addr,
+            Err(_) => return Err(format!("RPC address {} is not valid", CONFIG.rpc_addr)),
+        };
+
+        //stop accepting connections on shutdown and give open requests some seconds to finish
+        let handle = Handle::new();
+        let shutdown_handle = handle.clone();
+        let waiter = tokio::spawn(async move {
+            Shutdown::token().cancelled().await;
+            shutdown_handle.graceful_shutdown(Some(Duration::from_secs(10)));
+        });
+
+        let served = axum_server::bind(addr)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await;
+        waiter.abort();
+        served.map_err(|e| format!("error from RPC server:\n{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::{Request, StatusCode}};
+    use tower::ServiceExt;
+
+    use crate::relay::tools::metrics::METRICS;
+
+    use super::*;
+
+    //count of requests in latency histogram of a route
+    fn requests(route: &str) -> u64 {
+        let series = format!("centichain_rpc_latency_seconds_count{{route=\"{}\"}} ", route);
+        METRICS
+            .encode()
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix(&series))
+            .map_or(0, |count| count.parse().unwrap())
+    }
 
-        match axum_server::bind(addr).serve(app.into_make_service()).await {
-            Ok(_) => {}
-            Err(e) => write_log(&format!("error from RPC server:\n{}", e)),
-        }
+    #[tokio::test]
+    async fn measures_latency_of_unmatched_requests() {
+        let before = requests("unmatched");
+        let request = Request::get("/no/such/route").body(Body::empty()).unwrap();
+        let response = Rpc::router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(requests("unmatched"), before + 1);
     }
 }
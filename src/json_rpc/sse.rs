@@ -9,10 +9,10 @@ use mongodb::{
 };
 use std::convert::Infallible;
 use std::pin::Pin;
+use tracing::error;
 
 use crate::relay::{
     practical::{block::block::Block, db::Mongodb},
-    tools::create_log::write_log,
 };
 
 // Handler for SSE
@@ -45,13 +45,13 @@ pub fn realtime_centis() -> BoxStream<'static, Result<Event, Infallible>> {
                         }
                     }
                     Err(e) => {
-                        write_log(&format!("Error in watching of mongodb: {:?}", e));
+                        error!("Error in watching of mongodb: {:?}", e);
                         yield Ok(Event::default().data("Error occurred while watching MongoDB"));
                     }
                 }
             }
             Err(e) => {
-                write_log(&format!("Error in connecting to mongodb: {:?}", e));
+                error!("Error in connecting to mongodb: {:?}", e);
                 yield Ok(Event::default().data("Error occurred while connecting to MongoDB"));
             }
         }
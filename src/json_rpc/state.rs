@@ -0,0 +1,25 @@
+use axum::Json;
+
+use crate::relay::practical::{commitment::UtxoCommitment, db::Mongodb};
+
+use super::server::StateRes;
+
+//commitment of utxo set after the last block of relay
+pub async fn handle_state() -> Json<StateRes> {
+    match Mongodb::connect().await {
+        Ok(db) => match UtxoCommitment::last(&db).await {
+            Ok(commitment) => Json(StateRes {
+                commitment,
+                status: "".to_string(),
+            }),
+            Err(e) => Json(StateRes {
+                commitment: None,
+                status: e.to_string(),
+            }),
+        },
+        Err(_) => Json(StateRes {
+            commitment: None,
+            status: "Relay has problem! try with anothers.".to_string(),
+        }),
+    }
+}
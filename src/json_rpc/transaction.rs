@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use axum::{
     extract::{self},
     Json,
@@ -9,9 +7,8 @@ use mongodb::{
     bson::{to_document, Document},
     Collection,
 };
-use rust_decimal::Decimal;
 
-use crate::relay::practical::{db::Mongodb, transaction::Transaction};
+use crate::relay::practical::{chain_params::CHAIN_PARAMS, db::Mongodb, transaction::Transaction};
 
 use super::server::TxRes;
 
@@ -28,7 +25,7 @@ pub async fn handle_transaction(
         Ok(db) => {
             let trx_coll: Collection<Document> = db.collection("transactions");
             transaction.date.clear();
-            transaction.fee = transaction.value * Decimal::from_str("0.01").unwrap();
+            transaction.fee = CHAIN_PARAMS.fee(transaction.value);
             transaction
                 .date
                 .push_str(&Utc::now().round_subsecs(0).to_string());
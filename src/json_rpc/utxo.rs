@@ -12,7 +12,9 @@ use mongodb::{
 use rust_decimal::Decimal;
 
 // Import custom modules for database and UTXO handling
-use crate::relay::{practical::db::Mongodb, tools::{create_log::write_log, utxo::Person}};
+use tracing::error;
+
+use crate::relay::{practical::db::Mongodb, tools::utxo::Person};
 
 use super::server::ReqForUtxo;
 
@@ -96,13 +98,13 @@ async fn onchange(mut socket: WebSocket) {
                                                 .await
                                                 .is_err()
                                             {
-                                                write_log("Error sending message to client-(RPC-server/ reciept.rs/ 53)");
+                                                error!("Error sending message to client-(RPC-server/ reciept.rs/ 53)");
                                                 break;
                                             }
                                         }
                                     }
                                 } else {
-                                    write_log(
+                                    error!(
                                 "Failed to create change stream-(RPC-server/ reciept.rs/ 61)",
                             );
                                 }
@@ -118,7 +120,7 @@ async fn onchange(mut socket: WebSocket) {
             });
         }
         Err(_) => {
-            write_log("Error connecting to MongoDB-(RPC-server/ utxo.rs/ 43)");
+            error!("Error connecting to MongoDB-(RPC-server/ utxo.rs/ 43)");
         }
     }
 }
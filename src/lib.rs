@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod json_rpc;
+pub mod middle_gossipper;
+pub mod relay;
+
+//clients of centichain use these without running a relay
+pub use relay::practical::{builder::TransactionBuilder, light::LightClient};
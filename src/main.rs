@@ -1,27 +1,120 @@
-mod relay;
-mod json_rpc;
-use relay::practical::db::Mongodb;
-use relay::tools::create_log::write_log;
-use middle_gossipper::check_mongo_changes::MiddleGossipper;
-mod middle_gossipper;
-use relay::Relay;
-use json_rpc::Rpc;
+use relay_node::commands;
+use relay_node::relay::practical::{
+    chain_params::Network, config::CONFIG, db::Mongodb, genesis::GenesisSpec, keystore::Keystore,
+    swarm::CentichainBehaviour,
+};
+use relay_node::relay::tools::{
+    logger::Logger,
+    shutdown::Shutdown,
+    supervisor::{Policy, Restart, Supervisor},
+};
+use std::time::Duration;
+use tracing::{error, info};
+use relay_node::middle_gossipper::check_mongo_changes::MiddleGossipper;
+use relay_node::relay::Relay;
+use relay_node::json_rpc::{AdminApi, Rpc};
 
 #[tokio::main]
 async fn main() {
+    //logs go to stderr if the configured destination can't be used
+    if let Err(e) = Logger::init() {
+        eprintln!("Logging problem: {}", e);
+    }
+
+    //an unknown network stops relay before any part of it uses parameters of network
+    if let Err(e) = Network::from_env() {
+        error!("{}", e);
+        std::process::exit(1)
+    }
+
+    //an incorrect genesis file stops relay before any part of it uses the genesis
+    if let Err(e) = GenesisSpec::load(&CONFIG.genesis_path) {
+        error!("{}", e);
+        std::process::exit(405)
+    }
+
+    //run command if relay started with a command like init-devnet
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return commands::run(&args).await;
+    }
+
+    //unlock keystore for signing if it was configured, else read wallet file to get wallet address of relay
+    //it's important for handshaking requests from validators
+    //passphrase may be read from terminal, so it doesn't block threads of runtime
+    let wallet = match &CONFIG.keystore_path {
+        Some(path) => {
+            let path = path.clone();
+            match tokio::task::spawn_blocking(move || Keystore::unlock(&path)).await {
+                Ok(unlocked) => unlocked,
+                Err(e) => Err(format!("Unlocking keystore stopped: {}", e)),
+            }
+        }
+        None => CONFIG.wallet(),
+    };
+    let wallet = match wallet {
+        Ok(wallet) => wallet,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1)
+        }
+    };
+
+    //a missing or incorrect keypair file stops relay before it joins the network
+    if let Err(e) = CentichainBehaviour::keypair() {
+        error!("{}", e);
+        std::process::exit(1)
+    }
+
     match Mongodb::connect().await {
         Ok(db) => {
-            let (_, _, _) = tokio::join!(
-                Rpc::handle_requests(),
-                MiddleGossipper::checker(&db),
-                Relay::start(&db)
+            //each part runs as a supervised task that is restarted when it fails
+            //all of them stop together on SIGINT/SIGTERM or when one of them fails for good
+            let mut supervisor = Supervisor::new();
+            supervisor.spawn(
+                "rpc",
+                Policy::new(Restart::OnFailure).max_restarts(5),
+                Rpc::handle_requests,
+            );
+            //admin api is for operators and runs only if its token is configured
+            match &CONFIG.admin_token {
+                Some(token) => {
+                    let token = token.clone();
+                    supervisor.spawn(
+                        "admin",
+                        Policy::new(Restart::OnFailure).max_restarts(5),
+                        move || AdminApi::serve(token.clone()),
+                    );
+                }
+                None => info!("Admin api is disabled, set CENTICHAIN_ADMIN_TOKEN to enable it."),
+            }
+            let gossiper_db = db.clone();
+            supervisor.spawn(
+                "middle-gossiper",
+                Policy::new(Restart::Always)
+                    .backoff(Duration::from_secs(5), Duration::from_secs(60)),
+                move || {
+                    let db = gossiper_db.clone();
+                    async move { MiddleGossipper::checker(&db).await }
+                },
             );
+            supervisor.spawn(
+                "relay",
+                Policy::new(Restart::OnFailure).max_restarts(5),
+                move || {
+                    let db = db.clone();
+                    async move { Relay::start(&db, wallet).await }
+                },
+            );
+            tokio::join!(Shutdown::signals(), supervisor.wait());
+            info!("Relay stopped.");
+            std::process::exit(Shutdown::exit_code())
         }
         Err(e) => {
-            write_log(&format!(
+            error!(
                 "mongodb connection has problem! program closed.\n{}",
                 e
-            ));
+            );
             std::process::exit(800)
         }
     }
@@ -11,11 +11,16 @@ use mongodb::{
     Collection, Database,
 };
 use tokio::time::sleep;
+use tracing::{info, warn};
 
 use crate::relay::{
-    events::{addresses::Listeners, requests::Requests},
+    events::{
+        addresses::Listeners,
+        handshake::{Handshake, HandshakeResponse},
+        requests::Requests,
+    },
     practical::{swarm::Req, transaction::Transaction},
-    tools::create_log::write_log,
+    tools::shutdown::Shutdown,
 };
 
 use super::middlegossiper_swarm::{MiddleSwarmConf, MyBehaviour, MyBehaviourEvent};
@@ -23,40 +28,54 @@ use super::middlegossiper_swarm::{MiddleSwarmConf, MyBehaviour, MyBehaviourEvent
 pub struct MiddleGossipper;
 
 impl MiddleGossipper {
-    pub async fn checker(db: &Database) {
+    pub async fn checker(db: &Database) -> Result<(), String> {
         //dialing to relay that is in the radsress collection(raddress means Relay Address)
         let mut swarm = MyBehaviour::new().await;
-        sleep(Duration::from_secs(60)).await; //delay to save addresses of relay to DB
+        let shutdown = Shutdown::token();
+        //delay to save addresses of relay to DB
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = sleep(Duration::from_secs(60)) => {}
+        }
         let collection: Collection<Document> = db.collection("raddress");
-        let addr_doc = collection.find_one(doc! {}).await.unwrap().unwrap(); // find relay address from database
-        let addresses: Listeners = from_document(addr_doc).unwrap(); // deserialize document to listener structure
-        let dial_address: Multiaddr = addresses.p2p.parse().unwrap(); // pars p2p of listener to multi address for dialing
-        swarm.dial(dial_address).unwrap(); // dialing
+        // find relay address from database
+        let addr_doc = match collection.find_one(doc! {}).await {
+            Ok(Some(doc)) => doc,
+            Ok(None) => return Err("Relay address is not saved yet".to_string()),
+            Err(e) => return Err(format!("Querying relay address problem: {}", e)),
+        };
+        let addresses: Listeners = from_document(addr_doc).map_err(|e| e.to_string())?; // deserialize document to listener structure
+        let dial_address: Multiaddr = addresses.p2p.parse().map_err(|_| "Relay address is not a multiaddress".to_string())?; // pars p2p of listener to multi address for dialing
+        swarm.dial(dial_address).map_err(|e| e.to_string())?; // dialing
 
         let mut connected_id = String::new();
+        let mut handshaked = false;
         loop {
-            match swarm.select_next_some().await {
+            let event = tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                event = swarm.select_next_some() => event,
+            };
+            match event {
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                    write_log("Middlegossipper connection stablished.");
+                    info!("Middlegossipper connection stablished.");
 
                     connected_id.push_str(&peer_id.to_string());
 
-                    match Self::wathcing(db, &mut swarm, peer_id).await {
-                        Ok(_) => {}
-                        Err(e) => {
-                            write_log(e);
-                            std::process::exit(0);
-                        }
-                    }
+                    //relay accepts transactions only after a handshake
+                    let handshake = Handshake::client(swarm.local_peer_id());
+                    let request = Req {
+                        req: serde_json::to_string(&Requests::Handshake(handshake)).unwrap(),
+                    };
+                    swarm.behaviour_mut().req_res.send_request(&peer_id, request);
                 }
 
                 SwarmEvent::OutgoingConnectionError { .. } => {
-                    write_log("Middlegossiper dialing error!");
-                    std::process::exit(0)
+                    return Err("Middlegossiper dialing error!".to_string());
                 }
 
                 SwarmEvent::ConnectionClosed { .. } => {
-                    write_log("Middlegossiper connection closed!");
+                    warn!("Middlegossiper connection closed!");
                 }
 
                 SwarmEvent::Behaviour(mybehaviour) => match mybehaviour {
@@ -65,16 +84,23 @@ impl MiddleGossipper {
                     },
                     MyBehaviourEvent::ReqRes(event) => match event {
                         Event::Message { message, .. } => match message {
-                            Message::Response { .. } => {
+                            Message::Response { response, .. } => {
                                 let peer_id: PeerId = connected_id.parse().unwrap();
-
-                                match Self::wathcing(db, &mut swarm, peer_id).await {
-                                    Ok(_) => {}
-                                    Err(e) => {
-                                        write_log(e);
-                                        std::process::exit(0);
+                                if !handshaked {
+                                    match serde_json::from_str::<HandshakeResponse>(&response.res) {
+                                        Ok(HandshakeResponse {
+                                            rejected: Some(e), ..
+                                        }) => {
+                                            return Err(format!("Middlegossiper handshake rejected: {}", e))
+                                        }
+                                        Ok(_) => handshaked = true,
+                                        Err(_) => {
+                                            return Err("Middlegossiper handshake response is incorrect".to_string())
+                                        }
                                     }
                                 }
+
+                                Self::wathcing(db, &mut swarm, peer_id).await?;
                             }
                             _ => {}
                         },
@@ -84,6 +110,7 @@ impl MiddleGossipper {
                 _ => {}
             }
         }
+        Ok(())
     }
 
     //wathcing mongo
@@ -96,13 +123,18 @@ impl MiddleGossipper {
         let pipeline = vec![doc! { "$match": {
             "operationType": "insert"
         }}];
-        let mut watchin = transactions_coll
-            .watch()
-            .pipeline(pipeline)
-            .await
-            .expect("error in mongodb watching");
+        let mut watchin = match transactions_coll.watch().pipeline(pipeline).await {
+            Ok(watchin) => watchin,
+            Err(_) => return Err("Error in starting of mongodb watching"),
+        };
 
-        if let Some(change) = watchin.next().await {
+        //waiting for a change must not block shutdown
+        let shutdown = Shutdown::token();
+        let change = tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            change = watchin.next() => change,
+        };
+        if let Some(change) = change {
             match change {
                 Ok(data) => {
                     if let Some(doc) = data.full_document {
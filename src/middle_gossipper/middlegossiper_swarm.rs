@@ -4,10 +4,10 @@ use libp2p::{
     identity::Keypair,
     request_response::{cbor, ProtocolSupport},
     swarm::NetworkBehaviour,
-    Multiaddr, StreamProtocol, Swarm, SwarmBuilder,
+    Multiaddr, Swarm, SwarmBuilder,
 };
 
-use crate::relay::practical::swarm::{Req, Res};
+use crate::relay::practical::swarm::{CentichainBehaviour, Req, Res};
 
 pub trait MiddleSwarmConf {
     async fn new() -> Pin<Box<Swarm<MyBehaviour>>>;
@@ -32,7 +32,7 @@ impl MiddleSwarmConf for MyBehaviour {
 
         //request and response protocol config
         let req_res = cbor::Behaviour::<Req, Res>::new(
-            [(StreamProtocol::new("/mg/1.0"), ProtocolSupport::Full)],
+            [(CentichainBehaviour::protocol(), ProtocolSupport::Full)],
             libp2p::request_response::Config::default(),
         );
 
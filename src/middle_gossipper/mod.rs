@@ -1,2 +1,2 @@
-pub mod middlegossiper_swarm;
+pub(crate) mod middlegossiper_swarm;
 pub mod check_mongo_changes;
\ No newline at end of file
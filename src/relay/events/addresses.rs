@@ -1,5 +1,9 @@
-use crate::relay::tools::create_log::write_log;
-use libp2p::{Multiaddr, PeerId};
+use std::net::IpAddr;
+
+use tracing::error;
+
+use crate::relay::practical::chain_params::{Network, CHAIN_PARAMS};
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 use mongodb::{
     bson::{doc, to_document, Document},
     Collection, Database,
@@ -27,13 +31,22 @@ impl PostListener {
 
 impl Listeners {
     //generate new listener structure with get new listener address and peer id
+    //it is none when the listener is not on public ip of relay
     pub async fn new<'a>(
         listener: &Multiaddr,
         peerid: &PeerId,
         db: &'a Database,
-    ) -> Result<Self, &'a str> {
+    ) -> Result<Option<Self>, &'a str> {
         let p2p = format!("{}/p2p/{}", listener.to_string(), peerid);
-        let public_ip = public_ip::addr().await;
+        //devnet relays run on local addresses, so the listener's own ip is used instead of public ip
+        let public_ip = if CHAIN_PARAMS.network == Network::Devnet {
+            listener.iter().find_map(|protocol| match protocol {
+                Protocol::Ip4(ip) if !ip.is_unspecified() => Some(IpAddr::V4(ip)),
+                _ => None,
+            })
+        } else {
+            public_ip::addr().await
+        };
         match public_ip {
             Some(ip) => {
                 if p2p.contains(&ip.to_string()) {
@@ -45,20 +58,13 @@ impl Listeners {
                     let listener_to_doc = to_document(&new_listener).unwrap();
                     match collection.delete_many(doc! {}).await {
                         Ok(_) => match collection.insert_one(listener_to_doc).await {
-                            Ok(_) => {}
-                            Err(e) => {
-                                write_log(&format!("problem with mongodb: {}", e));
-                                std::process::exit(0);
-                            }
+                            Ok(_) => Ok(Some(new_listener)),
+                            Err(_) => Err("Saving listener address problem-(relay/events/addresses 62)"),
                         },
-                        Err(e) => {
-                            write_log(&format!("problem with mongodb: {}", e));
-                            std::process::exit(0);
-                        }
+                        Err(_) => Err("Clearing listener address problem-(relay/events/addresses 64)"),
                     }
-                    Ok(new_listener)
                 } else {
-                    Err("")
+                    Ok(None)
                 }
             }
             None => Err("You don't have any public ips!"),
@@ -66,7 +72,12 @@ impl Listeners {
     }
 
     //post p2p address and ip address to the server as relay address and rpc address
-    pub async fn post<'a>(&self) -> Result<(), ()> {
+    pub async fn post<'a>(&self) -> Result<(), &'a str> {
+        //devnet relays are not posted to Centichain server
+        if CHAIN_PARAMS.network == Network::Devnet {
+            return Ok(());
+        }
+
         let client = Client::new();
 
         match client
@@ -83,16 +94,16 @@ impl Listeners {
                     .await
                 {
                     Ok(_) => Ok(()),
-                    Err(e) => Err(write_log(&format!(
-                        "Sending ip address to server error: {}",
-                        e
-                    ))),
+                    Err(e) => {
+                        error!("Sending ip address to server error: {}", e);
+                        Err("Sending ip address to server problem-(relay/events/addresses 99)")
+                    }
                 }
             }
-            Err(e) => Err(write_log(&format!(
-                "Sending p2p address to server error: {}",
-                e
-            ))),
+            Err(e) => {
+                error!("Sending p2p address to server error: {}", e);
+                Err("Sending p2p address to server problem-(relay/events/addresses 105)")
+            }
         }
     }
 }
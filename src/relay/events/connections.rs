@@ -1,8 +1,11 @@
-use libp2p::{gossipsub::IdentTopic, PeerId, Swarm};
+use std::collections::HashSet;
+
+use libp2p::{PeerId, Swarm};
 use mongodb::{
     bson::{doc, Document},
     Collection, Database,
 };
+use tracing::{error, info, instrument, warn};
 
 use crate::relay::{
     practical::{
@@ -12,8 +15,8 @@ use crate::relay::{
         swarm::CentichainBehaviour,
     },
     tools::{
-        create_log::write_log,
         syncer::{Sync, Syncer},
+        metrics::METRICS,
         waiting::Waiting,
         wrongdoer::WrongDoer,
         zipp::Zip,
@@ -23,9 +26,11 @@ use crate::relay::{
 use super::{addresses::Listeners, gossip_messages::GossipMessages};
 
 // Struct to handle and manage connections
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ConnectionsHandler {
     pub connections: Vec<Connection>,
+    //peers that their handshake was accepted, only they can send blocks and transactions
+    handshaked: HashSet<PeerId>,
 }
 
 // Enum to differentiate between types of connections
@@ -62,13 +67,30 @@ impl ConnectionsHandler {
     pub fn new() -> Self {
         Self {
             connections: Vec::new(),
+            handshaked: HashSet::new(),
         }
     }
 
+    // Remember a peer that its handshake was accepted
+    pub fn handshaked(&mut self, peerid: PeerId) {
+        self.handshaked.insert(peerid);
+    }
+
+    // Check if a peer has completed its handshake
+    pub fn is_handshaked(&self, peerid: &PeerId) -> bool {
+        self.handshaked.contains(peerid)
+    }
+
+    // Forget handshake of a peer when its connection is closed
+    pub fn forget(&mut self, peerid: &PeerId) {
+        self.handshaked.remove(peerid);
+    }
+
     // Add a new connection to the handler with the given PeerId
     fn push_new_connection(&mut self, peerid: PeerId) {
         let new_connection = Connection::new(peerid, None);
-        self.connections.push(new_connection)
+        self.connections.push(new_connection);
+        METRICS.connections(self);
     }
 
     // Update the kind of an existing connection identified by PeerId
@@ -80,12 +102,14 @@ impl ConnectionsHandler {
         {
             Some(i) => {
                 self.connections[i].update(kind); // Update kind if connection exists
+                METRICS.connections(self);
             }
             None => {}
         }
     }
 
     // Update dialed relays, synchronize with the network if not already synced, and handle new connections
+    #[instrument(name = "peer", skip_all, fields(peer = %connection_peerid))]
     pub async fn update_and_sync<'a>(
         &mut self,
         dialed_relays: &mut DialedRelays,
@@ -106,32 +130,30 @@ impl ConnectionsHandler {
             .is_some()
         {
             // Log successful connection with dialed relay
-            write_log(&format!(
+            info!(
                 "Connection established with this dialed relay: {}",
                 connection_peerid
-            ));
+            );
             // Synchronize if not already synced
             match sync_state {
                 Sync::Synced => Ok(()),
                 Sync::NotSynced => {
                     // Log start of syncing process
-                    write_log("start syncing...");
+                    info!("start syncing...");
                     if let Err(e) =
                         Syncer::syncing(db, recieved_blocks, last_block, dialed_relays, leader)
                             .await
                     {
                         // Log syncing error
-                        write_log(e);
+                        error!("{}", e);
                         Err(e)
                     } else {
                         sync_state.synced(); // Update sync state if successful
 
                         // Post new listeners after successful sync
                         match Listeners::new(&multiaddress.parse().unwrap(), peerid, db).await {
-                            Ok(listeners) => match listeners.post().await {
-                                Ok(_) => Ok(()),
-                                Err(_) => Err(""),
-                            },
+                            Ok(Some(listeners)) => listeners.post().await,
+                            Ok(None) => Err("Listener address is not on public ip-(relay/events/connections 156)"),
                             Err(e) => Err(e),
                         }
                     }
@@ -139,19 +161,23 @@ impl ConnectionsHandler {
             }
         } else {
             // Log connection with non-relay peer
-            write_log(&format!(
+            info!(
                 "Connection established with: {}",
                 connection_peerid
-            ));
+            );
             // Create a zip of the database for new connections
             match Zip::maker() {
-                Ok(_) => Ok(Self::push_new_connection(self, connection_peerid)),
+                Ok(_) => {
+                    self.push_new_connection(connection_peerid);
+                    Ok(())
+                }
                 Err(e) => Err(e),
             }
         }
     }
 
     // Remove a connection from the handler and database, and handle associated cleanup
+    #[instrument(name = "peer", skip_all, fields(peer = %peerid))]
     pub async fn remove<'a>(
         &mut self,
         db: &'a Database,
@@ -167,7 +193,7 @@ impl ConnectionsHandler {
                 // Attempt to disconnect the peer from the swarm
                 match swarm.disconnect_peer_id(peerid) {
                     Ok(_) => {
-                        write_log(&format!("connection closed and removed with: {}", peerid));
+                        warn!("connection closed and removed with: {}", peerid);
                     }
                     Err(_) => {}
                 }
@@ -179,6 +205,7 @@ impl ConnectionsHandler {
                     && self.connections[index].clone().kind.unwrap() == Kind::Relay
                 {
                     self.connections.remove(index);
+                    METRICS.connections(self);
                     let filter = doc! {"relay": peerid.to_string()};
                     // Remove associated validators and update waiting times
                     match collection.count_documents(filter.clone()).await {
@@ -201,12 +228,15 @@ impl ConnectionsHandler {
                                         let str_gossip_message =
                                             serde_json::to_string(&gossip_message).unwrap();
                                         if self.connections.len() > 1 {
-                                            match swarm.behaviour_mut().gossipsub.publish(IdentTopic::new("validator"), str_gossip_message.as_bytes()) {
-                                                Ok(_) => Ok(write_log(&format!(
-                                                    "connection closed and removed with: {}",
-                                                    peerid
-                                                ))),
-                                                Err(_) => Err("Failed to publish outnode message-(handlers/practical/connections.rs 198)")
+                                            match swarm.behaviour_mut().gossipsub.publish(CentichainBehaviour::topic("validator"), str_gossip_message.as_bytes()) {
+                                                Ok(_) => {
+                                                    warn!("connection closed and removed with: {}", peerid);
+                                                    Ok(())
+                                                }
+                                                Err(_) => {
+                                                    METRICS.gossip_failed("validator");
+                                                    Err("Failed to publish outnode message-(handlers/practical/connections.rs 198)")
+                                                }
                                             }
                                         } else {
                                             Ok(())
@@ -226,6 +256,7 @@ impl ConnectionsHandler {
                 } else {
                     // Remove connection and associated validator document
                     self.connections.remove(index);
+                    METRICS.connections(self);
                     match collection
                         .delete_one(doc! {"peerid": peerid.to_string()})
                         .await
@@ -236,15 +267,18 @@ impl ConnectionsHandler {
                             let str_gossip_message =
                                 serde_json::to_string(&gossip_message).unwrap();
                             if self.connections.len() > 1 {
-                                match swarm.behaviour_mut().gossipsub.publish(IdentTopic::new("validator"), str_gossip_message.as_bytes()) {
+                                match swarm.behaviour_mut().gossipsub.publish(CentichainBehaviour::topic("validator"), str_gossip_message.as_bytes()) {
                                     Ok(_) => {
-                                        write_log(&format!(
+                                        warn!(
                                             "connection closed and removed with: {}",
                                             peerid
-                                        ));
+                                        );
                                         Waiting::update(db, None).await
                                     },
-                                    Err(_) => Err("Failed to publish outnode message-(handlers/practical/connections.rs 224)")
+                                    Err(_) => {
+                                        METRICS.gossip_failed("validator");
+                                        Err("Failed to publish outnode message-(handlers/practical/connections.rs 224)")
+                                    }
                                 }
                             } else {
                                 Waiting::update(db, None).await
@@ -257,7 +291,10 @@ impl ConnectionsHandler {
                 }
             }
             None => match WrongDoer::remove(db, peerid).await {
-                Ok(removed) => Ok(write_log(&format!("Wrongdoer removed: {}", removed))),
+                Ok(removed) => {
+                    warn!("Wrongdoer removed: {}", removed);
+                    Ok(())
+                }
                 Err(e) => Err(e),
             },
         }
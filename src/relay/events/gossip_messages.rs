@@ -1,17 +1,22 @@
-use libp2p::{PeerId, Swarm};
+use libp2p::{identity::Keypair, PeerId, Swarm};
 use mongodb::{bson::{doc, Document}, Collection, Database};
 use serde::{Deserialize, Serialize};
+use sp_core::ed25519::Public;
+use tracing::{debug, instrument};
 
 use crate::relay::{
     practical::{
         block::{block::Block, message::BlockMessage},
+        evidence::Evidence,
+        finality::Attestation,
         leader::Leader,
         reciept::Reciept,
+        relay::RelayWallet,
         swarm::CentichainBehaviour,
         transaction::Transaction,
     },
     tools::{
-        create_log::write_log, syncer::{Sync, VSync}, wrongdoer::WrongDoer
+        syncer::{Sync, VSync}, wrongdoer::WrongDoer
     },
 };
 
@@ -25,12 +30,17 @@ pub enum GossipMessages {
     SyncMessage(VSync),
     LeaderVote(PeerId),
     Outnode(PeerId),
+    Attestation(Attestation),
+    RelayWallet(RelayWallet),
+    Evidence(Evidence),
 }
 
 impl GossipMessages {
     // Main handler for processing different types of gossip messages
+    #[instrument(name = "gossip", skip_all, fields(peer = %propagation_source))]
     pub async fn handle<'a>(
         message: Vec<u8>,
+        source: Option<PeerId>,
         propagation_source: PeerId,
         db: &'a Database,
         swarm: &mut Swarm<CentichainBehaviour>,
@@ -39,6 +49,8 @@ impl GossipMessages {
         sync_state: &Sync,
         recvied_blocks: &mut Vec<BlockMessage>,
         last_block: &mut Vec<Block>,
+        wallet: &Public,
+        keypair: &Keypair,
     ) -> Result<(), &'a str> {
         // Attempt to convert the message bytes to a UTF-8 string
         if let Ok(str_message) = String::from_utf8(message) {
@@ -47,7 +59,7 @@ impl GossipMessages {
                 match gossip_message {
                     // Handle block messages
                     GossipMessages::BlockMessage(block_message) => {
-                        write_log("Block message received");
+                        debug!("Block message received");
                         // Process the block message
                         block_message
                             .handle(
@@ -58,8 +70,11 @@ impl GossipMessages {
                                 last_block,
                                 leader,
                                 connections_handler,
+                                wallet,
+                                keypair,
                             )
                             .await
+                            .map(|_| ())
                     }
 
                     // Handle transactions
@@ -93,7 +108,18 @@ impl GossipMessages {
                     // Handle sync messages
                     GossipMessages::SyncMessage(vsync) => {
                         match sync_state {
-                            Sync::Synced => vsync.handle(db, leader).await, // Add new validator to validators document if it was a correct message
+                            // Add new validator to validators document if it was a correct and signed message
+                            Sync::Synced => {
+                                vsync
+                                    .handle(
+                                        db,
+                                        leader,
+                                        source,
+                                        propagation_source,
+                                        *swarm.local_peer_id(),
+                                    )
+                                    .await
+                            }
                             _ => Ok(()),
                         }
                     }
@@ -103,7 +129,7 @@ impl GossipMessages {
                         Sync::Synced => {
                             if leader.in_check {
                                 // Process the vote if the leader is being checked
-                                leader.check_votes(db, vote).await
+                                leader.check_votes(db, source, vote).await
                             } else {
                                 Ok(())
                             }
@@ -124,6 +150,22 @@ impl GossipMessages {
                             }
                         }
                     }
+
+                    // Handle attestations of relays about finalized blocks
+                    GossipMessages::Attestation(attestation) => match sync_state {
+                        Sync::Synced => attestation.handle(db).await,
+                        Sync::NotSynced => Ok(()),
+                    },
+
+                    // Handle wallets that relays announce for their fees
+                    GossipMessages::RelayWallet(relay_wallet) => relay_wallet.handle(db, source).await,
+
+                    // Handle evidences of validators that signed two blocks at one height
+                    GossipMessages::Evidence(evidence) => {
+                        evidence
+                            .handle(db, leader, connections_handler, swarm)
+                            .await
+                    }
                 }
             } else {
                 // Return Ok if the message couldn't be deserialized
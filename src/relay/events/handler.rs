@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use futures::StreamExt;
 use libp2p::{
-    gossipsub::Event as GossipsubEvent, request_response::Event as ReqResEvent, swarm::SwarmEvent,
-    PeerId, Swarm,
+    gossipsub::Event as GossipsubEvent, identity::Keypair, request_response::Event as ReqResEvent,
+    swarm::SwarmEvent, PeerId, Swarm,
 };
 use mongodb::Database;
 use sp_core::ed25519::Public;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
 
 use crate::relay::{
     events::{
@@ -15,23 +19,32 @@ use crate::relay::{
     },
     practical::{
         block::{block::Block, message::BlockMessage},
+        earnings::Earning,
         leader::Leader,
-        relay::{DialedRelays, First},
+        relay::{DialedRelays, First, RelayWallet},
         swarm::{CentichainBehaviour, CentichainBehaviourEvent},
     },
-    tools::{create_log::write_log, get_last_block::LastBlock, syncer::Sync},
+    tools::{
+        admin::{Admin, Bans},
+        get_last_block::LastBlock,
+        metrics::METRICS,
+        shutdown::Shutdown,
+        syncer::Sync,
+    },
 };
 
 pub struct State;
 
 impl State {
-    pub async fn handle(
+    //errors that relay can't continue with are returned to relay task, so its restart policy decides
+    pub async fn handle<'a>(
         swarm: &mut Swarm<CentichainBehaviour>,
-        db: &Database,
+        db: &'a Database,
         dialed_relays: &mut DialedRelays,
         peerid: &PeerId,
         wallet: &Public,
-    ) {
+        keypair: &Keypair,
+    ) -> Result<(), &'a str> {
         //Prerequisites
         let mut recieved_blocks: Vec<BlockMessage> = Vec::new();
         let mut multiaddress = String::new();
@@ -39,6 +52,7 @@ impl State {
         let mut last_block: Vec<Block> = Vec::new();
         let mut connections_handler = ConnectionsHandler::new();
         let mut leader = Leader::new(None);
+        let relay_wallet = RelayWallet::new(*peerid, *wallet);
 
         //fill last block at first
         match LastBlock::get(db).await {
@@ -47,20 +61,53 @@ impl State {
                     last_block.push(is_block.unwrap());
                 }
 
+                //peers that operator banned can't connect to this relay
+                if let Err(e) = Bans::apply(db, swarm).await {
+                    error!("{}", e);
+                }
+
                 //start handeling of events that recieve in p2p network with relays and validators
+                //shutdown is checked before every event, so an in-flight event(like applying a block) is finished first
+                //commands of admin api are answered between events
+                let shutdown = Shutdown::token();
+                let mut admin_commands = Admin::commands().await;
                 'handle_loop: loop {
-                    match swarm.select_next_some().await {
+                    let event = tokio::select! {
+                        biased;
+                        _ = shutdown.cancelled() => {
+                            Self::goodbye(swarm, peerid).await;
+                            break 'handle_loop;
+                        }
+                        Some(command) = admin_commands.recv() => {
+                            Admin::apply(
+                                command,
+                                db,
+                                swarm,
+                                &connections_handler,
+                                &leader,
+                                &sync_state,
+                                &last_block,
+                            )
+                            .await;
+                            continue 'handle_loop;
+                        }
+                        event = swarm.select_next_some() => event,
+                    };
+                    match event {
                         //handle listeners and addresses
                         SwarmEvent::NewListenAddr { address, .. } => {
                             //send addresses to server after generate new listener
                             //if it has error break from loop to handler(start fn)
-                            if let Ok(listener) = Listeners::new(&address, peerid, db).await {
+                            if let Some(listener) = Listeners::new(&address, peerid, db).await? {
                                 match dialed_relays.first {
                                     First::Yes => match listener.post().await {
                                         Ok(_) => {
                                             sync_state.synced();
+                                            if let Err(e) = relay_wallet.save(db).await {
+                                                error!("{}", e);
+                                            }
                                         }
-                                        Err(_) => std::process::exit(0),
+                                        Err(e) => return Err(e),
                                     },
                                     First::No => {
                                         multiaddress.push_str(&address.to_string())
@@ -87,11 +134,19 @@ impl State {
                             )
                             .await
                             {
-                                Ok(_) => {}
-                                Err(e) => {
-                                    write_log(e);
-                                    std::process::exit(0);
+                                Ok(_) => {
+                                    //save wallet of this relay after syncing because syncing replaces relays collection
+                                    //and index earnings of the synced blocks
+                                    if sync_state == Sync::Synced {
+                                        if let Err(e) = relay_wallet.save(db).await {
+                                            error!("{}", e);
+                                        }
+                                        if let Err(e) = Earning::index(db, wallet).await {
+                                            warn!("{}", e);
+                                        }
+                                    }
                                 }
+                                Err(e) => return Err(e),
                             }
                         }
 
@@ -105,16 +160,16 @@ impl State {
                             {
                                 match relay.clone().delete_req(dialed_relays).await {
                                     Ok(_) => {
-                                        write_log(&format!(
+                                        warn!(
                                             "Dialing failed with: {}",
                                             peer_id.unwrap()
-                                        ));
-                                        write_log(&format!("Relay Removed: {}", peer_id.unwrap()));
+                                        );
+                                        warn!("Relay Removed: {}", peer_id.unwrap());
                                         if dialed_relays.relays.len() < 1 {
                                             break 'handle_loop;
                                         }
                                     }
-                                    Err(e) => write_log(e),
+                                    Err(e) => error!("{}", e),
                                 }
                             }
                         }
@@ -123,13 +178,14 @@ impl State {
                         //remove closed connection from database as relay or validator
                         //break to dialing(mod) if there is no connection with atleast a relay
                         SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                            connections_handler.forget(&peer_id);
                             if leader.peerid.is_some() && peer_id == leader.peerid.unwrap() {
                                 match leader
                                     .start_voting(db, &mut connections_handler, swarm)
                                     .await
                                 {
                                     Ok(_) => {}
-                                    Err(e) => write_log(e),
+                                    Err(e) => error!("{}", e),
                                 }
                             } else {
                                 match connections_handler.remove(db, peer_id, swarm).await {
@@ -139,7 +195,7 @@ impl State {
                                         }
                                     }
                                     Err(e) => {
-                                        write_log(e);
+                                        error!("{}", e);
                                     }
                                 }
                             }
@@ -169,8 +225,9 @@ impl State {
                                                 &sync_state,
                                                 &mut last_block,
                                                 peer,
+                                                keypair,
                                             )
-                                            .await;
+                                            .await?;
                                         }
                                     }
                                     _ => {}
@@ -182,10 +239,12 @@ impl State {
                             CentichainBehaviourEvent::Gossipsub(event) => match event {
                                 //get new subsctiber and push it to connections if there was any connections
                                 GossipsubEvent::Subscribed { peer_id, topic } => {
-                                    if topic.to_string() == "relay".to_string() {
+                                    if topic == CentichainBehaviour::topic("relay").hash() {
                                         connections_handler.update_connection(peer_id, Kind::Relay);
+                                        //announce wallet of this relay to the new relay for checking coinbases
+                                        relay_wallet.announce(swarm);
                                     }
-                                    if topic.to_string() == "validator".to_string() {
+                                    if topic == CentichainBehaviour::topic("validator").hash() {
                                         connections_handler
                                             .update_connection(peer_id, Kind::Validator);
                                         swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
@@ -201,6 +260,7 @@ impl State {
                                 } => {
                                     match GossipMessages::handle(
                                         message.data,
+                                        message.source,
                                         propagation_source,
                                         db,
                                         swarm,
@@ -209,14 +269,13 @@ impl State {
                                         &sync_state,
                                         &mut recieved_blocks,
                                         &mut last_block,
+                                        wallet,
+                                        keypair,
                                     )
                                     .await
                                     {
                                         Ok(_) => {}
-                                        Err(e) => {
-                                            write_log(e);
-                                            std::process::exit(0)
-                                        }
+                                        Err(e) => return Err(e),
                                     }
                                 }
                                 _ => {}
@@ -225,8 +284,33 @@ impl State {
                         _ => {}
                     }
                 }
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    //tell peers that this relay is leaving and give swarm a moment to send the message
+    async fn goodbye(swarm: &mut Swarm<CentichainBehaviour>, peerid: &PeerId) {
+        let goodbye = serde_json::to_string(&GossipMessages::Outnode(*peerid)).unwrap();
+        for topic in ["relay", "validator"] {
+            if swarm
+                .behaviour_mut()
+                .gossipsub
+                .publish(CentichainBehaviour::topic(topic), goodbye.as_bytes())
+                .is_err()
+            {
+                METRICS.gossip_failed(topic);
+            }
+        }
+        let flush = sleep(Duration::from_secs(1));
+        tokio::pin!(flush);
+        loop {
+            tokio::select! {
+                _ = &mut flush => break,
+                _ = swarm.select_next_some() => {}
             }
-            Err(e) => write_log(e),
         }
+        info!("Goodbye sent to peers.");
     }
 }
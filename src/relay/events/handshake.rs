@@ -0,0 +1,230 @@
+use libp2p::PeerId;
+use mongodb::{
+    bson::{doc, Document},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::{ed25519::Public, Pair};
+use tracing::{info, warn};
+
+use crate::relay::{
+    practical::{
+        block::{block::Block, header::Sign},
+        chain_params::CHAIN_PARAMS,
+        keystore::Keystore,
+        leader::Leader,
+    },
+};
+
+//version of handshake and request response messages of this relay
+pub const PROTOCOL_VERSION: u32 = 2;
+//oldest version that this relay can still talk with
+pub const MIN_PROTOCOL_VERSION: u32 = 2;
+//features that this relay supports, peers get the common ones in response
+pub const FEATURES: [&str; 4] = ["transactions", "blocks", "attestations", "relay-wallets"];
+
+//role of the peer that sends a handshake
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Role {
+    Validator,
+    Relay,
+    Client,
+}
+
+//last block that a node has
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BestBlock {
+    pub number: u64,
+    pub hash: String,
+}
+
+//handshake that peers send to a relay before anything else
+//sign is signature of wallet over handshake payload of the sender peerid to prove ownership of wallet
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Handshake {
+    pub version: u32,
+    pub chain_id: String,
+    pub role: Role,
+    pub best_block: Option<BestBlock>,
+    pub features: Vec<String>,
+    pub wallet: Public,
+    pub sign: Sign,
+}
+
+// Structure for handshake response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HandshakeResponse {
+    pub version: u32,
+    pub chain_id: String,
+    pub wallet: String,
+    pub first_node: FirstChecker,
+    pub best_block: Option<BestBlock>,
+    pub features: Vec<String>,
+    pub needs_sync: bool,
+    pub rejected: Option<String>,
+    pub sign: Option<Sign>,
+}
+
+// Enum to indicate if the node is the first in the network
+#[derive(Debug, Serialize, Deserialize)]
+pub enum FirstChecker {
+    Yes,
+    No,
+}
+
+impl HandshakeResponse {
+    // Create a new HandshakeResponse with default FirstChecker as No
+    fn new(wallet: String, last_block: &[Block]) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            chain_id: CHAIN_PARAMS.chain_id.clone(),
+            wallet,
+            first_node: FirstChecker::No,
+            best_block: last_block.first().map(|block| BestBlock {
+                number: block.header.number,
+                hash: block.header.hash.clone(),
+            }),
+            features: Vec::new(),
+            needs_sync: false,
+            rejected: None,
+            sign: None,
+        }
+    }
+
+    // Set the node as the first node in the network
+    fn set_is_first(&mut self) {
+        self.first_node = FirstChecker::Yes
+    }
+}
+
+impl Handshake {
+    //payload that wallet of peer signs in handshake
+    pub fn payload(peerid: &PeerId) -> String {
+        CHAIN_PARAMS.signing_payload(&format!("handshake:{}", peerid))
+    }
+
+    //handshake of a client that has no wallet, like middle gossiper of relay
+    //it proves only the peerid, so it is signed with a wallet that is made for it
+    pub fn client(peerid: &PeerId) -> Self {
+        let (pair, _) = sp_core::ed25519::Pair::generate();
+        Self {
+            version: PROTOCOL_VERSION,
+            chain_id: CHAIN_PARAMS.chain_id.clone(),
+            role: Role::Client,
+            best_block: None,
+            features: vec!["transactions".to_string()],
+            wallet: pair.public(),
+            sign: Sign {
+                signatgure: pair.sign(Self::payload(peerid).as_bytes()),
+                key: pair.public(),
+            },
+        }
+    }
+
+    //check network, version and wallet signature of handshake
+    fn check(&self, sender: &PeerId) -> Result<(), &'static str> {
+        if self.chain_id != CHAIN_PARAMS.chain_id {
+            return Err("Handshake is from another network.");
+        }
+        if self.version < MIN_PROTOCOL_VERSION {
+            return Err("Handshake version is not supported.");
+        }
+        if self.sign.key != self.wallet
+            || !sp_core::ed25519::Pair::verify(
+                &self.sign.signatgure,
+                Self::payload(sender),
+                &self.sign.key,
+            )
+        {
+            return Err("Handshake signature is incorrect.");
+        }
+        Ok(())
+    }
+
+    // Handle handshake requests and make response of it
+    // relay signs handshake payload of its own peerid with its wallet if it has a keystore
+    pub async fn handle<'a>(
+        &self,
+        db: &'a Database,
+        wallet: &Public,
+        local_peerid: &PeerId,
+        sender: PeerId,
+        leader: &mut Leader,
+        last_block: &[Block],
+    ) -> Result<HandshakeResponse, &'a str> {
+        let mut handshake_reponse = HandshakeResponse::new(wallet.to_string(), last_block);
+        handshake_reponse.sign = Keystore::sign(&Self::payload(local_peerid));
+
+        match self.check(&sender) {
+            Ok(_) => {
+                //common features of relay and peer
+                handshake_reponse.features = FEATURES
+                    .iter()
+                    .filter(|feature| self.features.iter().any(|f| f == *feature))
+                    .map(|feature| feature.to_string())
+                    .collect();
+
+                //peer needs syncing if its best block is behind best block of this relay
+                let peer_number = self.best_block.as_ref().map(|b| b.number).unwrap_or(0);
+                let relay_number = last_block.first().map(|b| b.header.number).unwrap_or(0);
+                handshake_reponse.needs_sync = peer_number < relay_number;
+
+                // Check blocks count and validators count from DB
+                let b_collection: Collection<Document> = db.collection("Blocks");
+                let v_collection: Collection<Document> = db.collection("validators");
+                let blocks_count = match b_collection.count_documents(doc! {}).await {
+                    Ok(count) => count,
+                    Err(_) => return Err("Counting blocks problem-(relay/events/handshake 177)"),
+                };
+                let validators_count = match v_collection.count_documents(doc! {}).await {
+                    Ok(count) => count,
+                    Err(_) => {
+                        return Err("Counting validators problem-(relay/events/handshake 182)")
+                    }
+                };
+
+                // If there are no blocks and validators, set this node as the first node
+                if blocks_count == 0 && validators_count == 0 && self.role == Role::Validator {
+                    info!("this is the first node");
+                    handshake_reponse.set_is_first();
+                    leader.update(Some(sender));
+                }
+            }
+            Err(e) => {
+                warn!("Handshake of {} rejected: {}", sender, e);
+                handshake_reponse.rejected = Some(e.to_string());
+            }
+        }
+
+        Ok(handshake_reponse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mongodb::{options::ClientOptions, Client};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handshake_fails_when_database_fails() {
+        let mut options = ClientOptions::parse("mongodb://127.0.0.1:1").await.unwrap();
+        options.server_selection_timeout = Some(Duration::from_millis(100));
+        let db = Client::with_options(options).unwrap().database("Centichain_test");
+
+        let sender = PeerId::random();
+        let mut handshake = Handshake::client(&sender);
+        handshake.role = Role::Validator;
+        let wallet = sp_core::ed25519::Pair::generate().0.public();
+        let mut leader = Leader::new(None);
+
+        let result = handshake
+            .handle(&db, &wallet, &PeerId::random(), sender, &mut leader, &[])
+            .await;
+        assert!(result.is_err());
+        //a relay that can't count its blocks must not take the peer as first node
+        assert_eq!(leader.peerid, None);
+    }
+}
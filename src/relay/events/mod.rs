@@ -1,5 +1,6 @@
 pub mod addresses;
 pub mod connections;
 pub mod gossip_messages;
+pub mod handshake;
 pub mod requests;
 pub mod handler;
\ No newline at end of file
@@ -1,65 +1,43 @@
-use libp2p::{gossipsub::IdentTopic, request_response::ResponseChannel, PeerId, Swarm};
+use libp2p::{identity::Keypair, request_response::ResponseChannel, PeerId, Swarm};
 use mongodb::{
     bson::{doc, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
 use sp_core::ed25519::Public;
+use tracing::{debug, error, instrument, warn};
 
 use crate::relay::{
     practical::{
         block::{block::Block, message::BlockMessage},
         leader::Leader,
+        light::HeadersRequest,
         reciept::Reciept,
         swarm::{CentichainBehaviour, Req, Res},
         transaction::Transaction,
     },
-    tools::{create_log::write_log, syncer::Sync},
+    tools::{metrics::METRICS, syncer::Sync},
 };
 
-use super::{connections::ConnectionsHandler, gossip_messages::GossipMessages};
+use super::{
+    connections::ConnectionsHandler, gossip_messages::GossipMessages, handshake::Handshake,
+};
 
 // Enum representing different types of requests that can be handled
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Requests {
-    Handshake(String),
+    Handshake(Handshake),
     BlockMessage(BlockMessage),
     Transaction(Transaction),
-}
-
-// Structure for handshake response
-#[derive(Debug, Serialize)]
-struct HandshakeResponse {
-    wallet: String,
-    first_node: FirstChecker,
-}
-
-// Enum to indicate if the node is the first in the network
-#[derive(Debug, Serialize)]
-enum FirstChecker {
-    Yes,
-    No,
-}
-
-impl HandshakeResponse {
-    // Create a new HandshakeResponse with default FirstChecker as No
-    fn new(wallet: String) -> Self {
-        Self {
-            wallet,
-            first_node: FirstChecker::No,
-        }
-    }
-
-    // Set the node as the first node in the network
-    fn set_is_first(&mut self) {
-        self.first_node = FirstChecker::Yes
-    }
+    Headers(HeadersRequest),
 }
 
 impl Requests {
     // Main handler for processing different types of requests
-    pub async fn handler(
-        db: &Database,
+    // database errors are returned to the relay task instead of stopping relay
+    #[instrument(name = "request", skip_all, fields(peer = %sender))]
+    pub async fn handler<'a>(
+        db: &'a Database,
         request: Req,
         channel: ResponseChannel<Res>,
         swarm: &mut Swarm<CentichainBehaviour>,
@@ -70,29 +48,61 @@ impl Requests {
         sync_state: &Sync,
         last_block: &mut Vec<Block>,
         sender: PeerId,
-    ) {
+        keypair: &Keypair,
+    ) -> Result<(), &'a str> {
         // Parse the request and handle it based on its type
         if let Ok(request_model) = serde_json::from_str::<Self>(&request.req) {
             match request_model {
-                // Handle handshake request
-                Requests::Handshake(msg) => {
-                    if msg == "handshake".to_string() {
-                        match Self::handshaker(
-                            swarm,
-                            db,
-                            wallet.to_string(),
-                            channel,
-                            sender,
-                            leader,
-                        )
-                        .await
-                        {
-                            Ok(_) => {}
-                            Err(e) => write_log(e),
-                        }
+                // Handle handshake request, peers with another chain or an old version are refused
+                Requests::Handshake(handshake) => {
+                    let handshake_response = handshake
+                        .handle(db, wallet, swarm.local_peer_id(), sender, leader, last_block)
+                        .await?;
+                    let rejected = handshake_response.rejected.is_some();
+
+                    // Serialize response and send it
+                    let str_response = serde_json::to_string(&handshake_response).unwrap();
+                    let response = Res::new(str_response);
+                    match swarm
+                        .behaviour_mut()
+                        .reqres
+                        .send_response(channel, response)
+                    {
+                        Ok(_) => {}
+                        Err(_) => error!(
+                            "Sending handshake response error-(relay/events/requests 73)",
+                        ),
+                    }
+
+                    // Refused peers are disconnected, accepted ones can send blocks and transactions
+                    if rejected {
+                        let _ = swarm.disconnect_peer_id(sender);
+                    } else {
+                        connections_handler.handshaked(sender);
+                    }
+                }
+
+                // Handle headers request of light clients
+                Requests::Headers(headers_request) => {
+                    let headers_response = headers_request.handle(db).await;
+                    let str_response = serde_json::to_string(&headers_response).unwrap();
+                    if swarm
+                        .behaviour_mut()
+                        .reqres
+                        .send_response(channel, Res::new(str_response))
+                        .is_err()
+                    {
+                        error!("Sending headers response error-(relay/events/requests 85)");
                     }
                 }
 
+                // Blocks and transactions are refused from peers without a completed handshake
+                Requests::Transaction(_) | Requests::BlockMessage(_)
+                    if !connections_handler.is_handshaked(&sender) =>
+                {
+                    warn!("Request of {} refused, it has not completed a handshake.", sender);
+                }
+
                 // Handle transaction request
                 Requests::Transaction(transaction) => {
                     let collection: Collection<Document> = db.collection("reciepts");
@@ -108,74 +118,57 @@ impl Requests {
                                     {
                                         Ok(_) => {
                                             // Propagate the transaction to the network
+                                            // a failed gossip leaves it in mempool, so it is logged and not fatal
                                             let gossip_message =
                                                 GossipMessages::Transaction(transaction.clone());
                                             let str_gossip_message =
                                                 serde_json::to_string(&gossip_message).unwrap();
-                                            match swarm.behaviour_mut().gossipsub.publish(
-                                                IdentTopic::new("validator"),
+                                            if let Err(e) = swarm.behaviour_mut().gossipsub.publish(
+                                                CentichainBehaviour::topic("validator"),
                                                 str_gossip_message,
                                             ) {
+                                                METRICS.gossip_failed("validator");
+                                                error!("Gossiping transaction problem: {}", e);
+                                            }
+
+                                            // Insert receipt and send response
+                                            match Reciept::insertion(
+                                                None,
+                                                Some(&transaction),
+                                                None,
+                                                db,
+                                            )
+                                            .await
+                                            {
                                                 Ok(_) => {
-                                                    // Insert receipt and send response
-                                                    match Reciept::insertion(
-                                                        None,
-                                                        Some(&transaction),
-                                                        None,
-                                                        db,
-                                                    )
-                                                    .await
+                                                    let response = Res {
+                                                        res: "".to_string(),
+                                                    };
+                                                    if swarm
+                                                        .behaviour_mut()
+                                                        .reqres
+                                                        .send_response(channel, response)
+                                                        .is_err()
                                                     {
-                                                        Ok(_) => {
-                                                            let response = Res {
-                                                                res: "".to_string(),
-                                                            };
-                                                            match swarm
-                                                                .behaviour_mut()
-                                                                .reqres
-                                                                .send_response(channel, response)
-                                                            {
-                                                                Ok(_) => {}
-                                                                Err(_e) => {
-                                                                    write_log(
-                                                                        "Sending transaction response error!",
-                                                                    );
-                                                                    std::process::exit(0)
-                                                                }
-                                                            }
-                                                        }
-                                                        Err(e) => {
-                                                            write_log(e);
-                                                            std::process::exit(0)
-                                                        }
+                                                        error!("Sending transaction response error-(relay/events/requests 139)");
                                                     }
                                                 }
-                                                Err(e) => {
-                                                    write_log(&format!(
-                                                        "Gossiping transaction problem: {}",
-                                                        e.to_string()
-                                                    ));
-                                                    std::process::exit(0);
-                                                }
+                                                Err(e) => return Err(e),
                                             }
                                         }
-                                        Err(e) => {
-                                            write_log(e);
-                                            std::process::exit(0);
-                                        }
+                                        Err(e) => return Err(e),
                                     },
                                     Err(e) => {
                                         match connections_handler.remove(db, sender, swarm).await {
-                                            Ok(_) => write_log(e),
-                                            Err(e) => write_log(e),
+                                            Ok(_) => error!("{}", e),
+                                            Err(e) => error!("{}", e),
                                         }
                                     }
                                 }
                             }
                         }
-                        Err(e) => {
-                            write_log(&format!("Error checking transaction in collection: {}", e));
-                            std::process::exit(0);
+                        Err(_) => {
+                            return Err("Querying reciepts problem-(relay/events/requests 171)");
                         }
                     }
                 }
@@ -191,10 +184,13 @@ impl Requests {
                             last_block,
                             leader,
                             connections_handler,
+                            wallet,
+                            keypair,
                         )
                         .await
                     {
-                        Ok(_) => {
+                        Ok(false) => {}
+                        Ok(true) => {
                             // Propagate the block message to the network
                             let gossip_message = GossipMessages::BlockMessage(block_message);
                             let str_gossip_message =
@@ -202,65 +198,22 @@ impl Requests {
                             match swarm
                                 .behaviour_mut()
                                 .gossipsub
-                                .publish(IdentTopic::new("validator"), str_gossip_message)
+                                .publish(CentichainBehaviour::topic("validator"), str_gossip_message)
                             {
                                 Ok(_) => {
-                                    write_log("block message gossiped");
+                                    debug!("block message gossiped");
                                 }
                                 Err(e) => {
-                                    write_log(&format!(
-                                        "Gossiping block message problem: {}",
-                                        e.to_string()
-                                    ));
-                                    std::process::exit(0);
+                                    METRICS.gossip_failed("validator");
+                                    error!("Gossiping block message problem: {}", e);
                                 }
                             }
                         }
-                        Err(e) => {
-                            write_log(e);
-                            std::process::exit(0);
-                        }
+                        Err(e) => return Err(e),
                     }
                 }
             }
         }
-    }
-
-    // Handle handshake requests
-    async fn handshaker<'a>(
-        swarm: &mut Swarm<CentichainBehaviour>,
-        db: &'a Database,
-        wallet: String,
-        channel: ResponseChannel<Res>,
-        sender: PeerId,
-        leader: &mut Leader,
-    ) -> Result<(), &'a str> {
-        let mut handshake_reponse = HandshakeResponse::new(wallet);
-
-        // Check blocks count and validators count from DB
-        let b_collection: Collection<Document> = db.collection("Blocks");
-        let v_collection: Collection<Document> = db.collection("validators");
-        let blocks_count = b_collection.count_documents(doc! {}).await.unwrap();
-        let validators_count = v_collection.count_documents(doc! {}).await.unwrap();
-
-        // If there are no blocks and validators, set this node as the first node
-        if blocks_count == 0 && validators_count == 0 {
-            write_log("this is the first node");
-            handshake_reponse.set_is_first();
-            leader.update(Some(sender));
-        }
-
-        // Serialize response and send it
-        let str_response = serde_json::to_string(&handshake_reponse).unwrap();
-        let response = Res::new(str_response);
-
-        match swarm
-            .behaviour_mut()
-            .reqres
-            .send_response(channel, response)
-        {
-            Ok(_) => Ok(()),
-            Err(_) => Err("Sending handshake response error-(handlers/practical/requests 188)"),
-        }
+        Ok(())
     }
 }
@@ -1,16 +1,31 @@
+use std::collections::HashSet;
+
+use libp2p::PeerId;
 use mongodb::{
-    bson::{doc, to_document, Document},
+    bson::{doc, from_document, to_document, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
 use sp_core::Pair;
+use tracing::error;
 
 use crate::relay::{
-    practical::transaction::Transaction,
-    tools::{utxo::UTXO, waiting::Waiting, HashMaker, create_log::write_log},
+    practical::{
+        chain_params::CHAIN_PARAMS, commitment::UtxoCommitment, finality::Checkpoint,
+        genesis::GenesisSpec, stake::Stake, transaction::{Transaction, TransactionError},
+    },
+    tools::{
+        merkel::{MerkelProof, MerkelRoot},
+        utxo::UTXO,
+        waiting::Waiting,
+        HashMaker,
+    },
 };
 
-use super::{coinbase::Coinbase, header::Header};
+use super::{
+    coinbase::{Coinbase, CoinbaseError},
+    header::{Header, HeaderError},
+};
 
 // Define the structure of a block, including its header and body.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -27,205 +42,315 @@ pub struct Body {
     pub transactions: Vec<Transaction>,
 }
 
+// Define the changes that a block makes to the state, they are made from the state of its parent
+// before changing anything, so applying them again after a stop in the middle gives the same state.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Changes {
+    pub commitment: UtxoCommitment,
+    pub waiting: Vec<(PeerId, u64)>,
+}
+
+// Define the reasons that a block can be rejected for.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BlockError<'a> {
+    // The block breaks a rule that its validator signed, so the validator is punished for it
+    Fault(&'a str),
+    // The block is rejected but its validator can't be blamed, like a wrong signature or a stale block
+    Rejected(&'a str),
+    // This relay couldn't check the block, like a problem of its database
+    Internal(&'a str),
+}
+
+impl<'a> BlockError<'a> {
+    pub fn message(&self) -> &'a str {
+        match self {
+            Self::Fault(e) | Self::Rejected(e) | Self::Internal(e) => e,
+        }
+    }
+}
+
 impl Block {
+    // Make inclusion proof of a transaction of the block against merkel root of its header
+    // Blocks below upgrade height have legacy merkel roots that don't have proofs
+    pub fn proof(&self, trx_hash: &str) -> Option<MerkelProof> {
+        if !CHAIN_PARAMS.upgraded(self.header.number) {
+            return None;
+        }
+        let hashes = self.body.transactions.iter().map(|trx| &trx.hash).collect();
+        MerkelRoot::proof(hashes, trx_hash)
+    }
+
+    // Check the block and apply it to the state of this relay
     pub async fn validation<'a>(
         &self,
         last_block: &mut Vec<Self>,
         db: &'a Database,
-    ) -> Result<&Self, &'a str> {
+    ) -> Result<&Self, BlockError<'a>> {
+        let changes = self.check(last_block, db).await?;
+        self.apply(&changes, db).await.map_err(BlockError::Internal)
+    }
+
+    // Check the block against the state of its parent without changing anything
+    // and make the changes that applying it makes to the state
+    pub async fn check<'a>(
+        &self,
+        last_block: &mut Vec<Self>,
+        db: &'a Database,
+    ) -> Result<Changes, BlockError<'a>> {
+        // Refuse blocks that would replace a block at or below the last finalized checkpoint
+        match Checkpoint::last(db).await {
+            Ok(Some(checkpoint)) if self.header.number <= checkpoint.number => {
+                let error_msg = "Block is below the last finalized checkpoint and rejected.";
+                error!("{}", error_msg);
+                return Err(BlockError::Rejected(error_msg));
+            }
+            Ok(_) => {}
+            Err(e) => return Err(BlockError::Internal(e)),
+        }
+
         // Check if the block is either the genesis block or if it correctly follows the last block
-        if (last_block.len() > 0 && last_block[0].header.hash == self.header.previous)
-            || self.header.previous == "This Is The Genesis Block".to_string()
+        let follows = last_block
+            .first()
+            .is_some_and(|last| last.header.hash == self.header.previous);
+        if !follows && self.header.previous != GenesisSpec::previous() {
+            let error_msg = "Block validation problem!, previous hash doesn't match and Block rejected.";
+            error!("{}", error_msg);
+            return Err(BlockError::Rejected(error_msg));
+        }
+
+        // Check the block's signature to ensure its integrity
+        let hash_data = serde_json::to_string(&self.body).unwrap();
+        let hash = HashMaker::generate(&hash_data);
+        let sign_check = sp_core::ed25519::Pair::verify(
+            &self.header.signature.signatgure,
+            self.header.payload(&hash),
+            &self.header.signature.key,
+        );
+        if !sign_check {
+            let error_msg = "Block signature is wrong and Block rejected.";
+            error!("{}", error_msg);
+            return Err(BlockError::Rejected(error_msg));
+        }
+
+        // Validate the header's fields before validating the block's body
+        if let Err(e) = self
+            .header
+            .validation(last_block, &hash, &self.body.coinbase, db)
+            .await
         {
-            // Check the block's signature to ensure its integrity
-            let hash_data = serde_json::to_string(&self.body).unwrap();
-            let hash = HashMaker::generate(&hash_data);
-            let sign_check = sp_core::ed25519::Pair::verify(
-                &self.header.signature.signatgure,
-                hash,
-                &self.header.signature.key,
-            );
-
-            // If the block's signature is valid, proceed to validate the transactions in the block's body
-            if sign_check {
-                let mut trx_err = None;
-                let mut trx_backup: Vec<Transaction> = Vec::new();
-                let trx_collection: Collection<Document> = db.collection("transactions");
-
-                // Validate each transaction in the block's body
-                for i in 0..self.body.transactions.len() {
-                    let filter = doc! {"hash": self.body.transactions[i].hash.clone()};
-                    let query = trx_collection.find_one(filter).await;
-                    match query {
-                        Ok(opt) => match opt {
-                            None => {
-                                match Transaction::validate(&self.body.transactions[i], db).await {
-                                    Ok(transaction) => {
-                                        match trx_collection
-                                            .delete_one(doc! {"hash": &transaction.hash})
-                                            .await
-                                        {
-                                            Ok(_) => {
-                                                trx_backup.push(transaction.clone());
-                                            }
-                                            Err(e) => {
-                                                write_log(&format!("Error deleting transaction: {}", e));
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        write_log(&format!("Transaction validation error: {}", e));
-                                        trx_err.get_or_insert(e);
-                                        break;
-                                    }
-                                }
-                            }
-                            Some(_) => {
-                                match trx_collection
-                                    .delete_one(doc! {"hash": &self.body.transactions[i].hash})
-                                    .await
-                                {
-                                    Ok(_) => {
-                                        trx_backup.push(self.body.transactions[i].clone());
-                                    }
-                                    Err(e) => {
-                                        write_log(&format!("Error deleting existing transaction: {}", e));
-                                    }
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            let error_msg = "Querying transaction problem-(relay/practical/block 61)";
-                            write_log(&format!("{}: {}", error_msg, e));
-                            trx_err.get_or_insert(error_msg);
-                        }
-                    }
+            let error_msg = e.message();
+            error!("{}", error_msg);
+            // Number and merkel root are signed by the validator from upgrade height
+            return Err(match e {
+                HeaderError::Query => BlockError::Internal(error_msg),
+                HeaderError::Number | HeaderError::Merkel
+                    if CHAIN_PARAMS.upgraded(self.header.number) =>
+                {
+                    BlockError::Fault(error_msg)
                 }
+                _ => BlockError::Rejected(error_msg),
+            });
+        }
 
-                // If no transaction errors were found, validate the coinbase transaction and generate UTXOs
-                if trx_err.is_none() {
-                    match Coinbase::validation(
-                        &self.body.coinbase,
-                        last_block,
-                        &self.body.transactions,
-                    )
-                    .await
-                    {
-                        Ok(_) => {
-                            let mut utxo_err: Option<&str> = None;
-                            // Generate new UTXOs for each unspent output in the coinbase transaction
-                            for unspent in &self.body.coinbase.output.unspents {
-                                match UTXO::generate(
-                                    self.header.number,
-                                    &self.body.coinbase.hash,
-                                    &self.body.coinbase.output.hash,
-                                    unspent,
-                                    db,
-                                )
-                                .await
-                                {
-                                    Ok(_) => {}
-                                    Err(e) => {
-                                        write_log(&format!("Error generating UTXO for coinbase: {}", e));
-                                        utxo_err.get_or_insert(e);
-                                        break;
-                                    }
-                                }
-                            }
-
-                            // Generate new UTXOs for each unspent output in the block's transactions
-                            for trx in &self.body.transactions {
-                                for unspent in &trx.output.unspents {
-                                    match UTXO::generate(
-                                        self.header.number,
-                                        &trx.hash,
-                                        &trx.output.hash,
-                                        unspent,
-                                        db,
-                                    )
-                                    .await
-                                    {
-                                        Ok(_) => {}
-                                        Err(e) => {
-                                            write_log(&format!("Error generating UTXO for transaction: {}", e));
-                                            utxo_err.get_or_insert(e);
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-
-                            // If no UTXO generation errors occurred, update the waiting validators
-                            if utxo_err.is_none() {
-                                match Waiting::update(db, Some(&self.header.validator)).await {
-                                    Ok(_) => Ok(self),
-                                    Err(e) => {
-                                        write_log(&format!("Error updating waiting validators: {}", e));
-                                        // If updating the waiting validators fails, restore the transactions from the backup
-                                        for trx in trx_backup {
-                                            let trx_doc = to_document(&trx).unwrap();
-                                            match trx_collection.insert_one(trx_doc).await {
-                                                Ok(_) => {}
-                                                Err(e) => {
-                                                    write_log(&format!("Error restoring transaction: {}", e));
-                                                }
-                                            }
-                                        }
-                                        Err(e)
-                                    }
-                                }
-                            } else {
-                                // If UTXO generation fails, restore the transactions from the backup
-                                for trx in trx_backup {
-                                    let trx_doc = to_document(&trx).unwrap();
-                                    match trx_collection.insert_one(trx_doc).await {
-                                        Ok(_) => {}
-                                        Err(e) => {
-                                            write_log(&format!("Error restoring transaction after UTXO generation failure: {}", e));
-                                        }
-                                    }
-                                }
-                                Err(utxo_err.unwrap())
-                            }
-                        }
-                        Err(e) => {
-                            write_log(&format!("Coinbase validation error: {}", e));
-                            // If coinbase validation fails, restore the transactions from the backup
-                            for trx in trx_backup {
-                                let trx_doc = to_document(&trx).unwrap();
-                                match trx_collection.insert_one(trx_doc).await {
-                                    Ok(_) => {}
-                                    Err(e) => {
-                                        write_log(&format!("Error restoring transaction after coinbase validation failure: {}", e));
-                                    }
-                                }
-                            }
-                            Err(e)
+        // Commitment of utxo set after the block should match the header's utxo root
+        // it is made before changing anything, from utxo set of the parent
+        let commitment = UtxoCommitment::next(db, self)
+            .await
+            .map_err(BlockError::Internal)?;
+        // From upgrade height every header should carry the root
+        let root_error = match &self.header.utxo_root {
+            Some(root) if *root != commitment.root => {
+                Some("Block utxo root doesn't match the utxo set and Block rejected.")
+            }
+            None if CHAIN_PARAMS.upgraded(self.header.number) => {
+                Some("Block doesn't have a utxo root and Block rejected.")
+            }
+            _ => None,
+        };
+        if let Some(error_msg) = root_error {
+            error!("{}", error_msg);
+            return Err(BlockError::Fault(error_msg));
+        }
+
+        // Check all transactions against double spends and the UTXO set
+        if let Err(e) = Self::inputs_validation(&self.body.transactions, self.header.number, db).await {
+            let error_msg = e.message();
+            error!("Transaction validation error: {}", error_msg);
+            return Err(match e {
+                TransactionError::Invalid(_) => BlockError::Fault(error_msg),
+                TransactionError::Query(_) => BlockError::Internal(error_msg),
+            });
+        }
+
+        if let Err(e) = Coinbase::validation(
+            &self.body.coinbase,
+            last_block,
+            &self.body.transactions,
+            &self.header,
+            db,
+        )
+        .await
+        {
+            let error_msg = e.message();
+            error!("Coinbase validation error: {}", error_msg);
+            // Relay fee of an unknown relay wallet can't be checked the same by every relay,
+            // so the validator isn't blamed for it
+            return Err(match e {
+                CoinbaseError::Query(_) => BlockError::Internal(error_msg),
+                CoinbaseError::RelayWallet => BlockError::Rejected(error_msg),
+                _ => BlockError::Fault(error_msg),
+            });
+        }
+
+        let waiting = Waiting::next(db, Some(&self.header.validator))
+            .await
+            .map_err(BlockError::Internal)?;
+        Ok(Changes { commitment, waiting })
+    }
+
+    // Apply a checked block to the state of this relay
+    // Every step of it can run again for the same block, so a relay that stopped in the middle
+    // of a block finishes it by applying it again
+    pub async fn apply<'a>(&self, changes: &Changes, db: &'a Database) -> Result<&Self, &'a str> {
+        let mut trx_backup: Vec<Transaction> = Vec::new();
+        let trx_collection: Collection<Document> = db.collection("transactions");
+
+        // Spend inputs of transactions and remove the block's transactions from the mempool
+        // inputs of transactions that this relay admitted before were spent at their admission
+        for transaction in &self.body.transactions {
+            for utxo in transaction.inputs() {
+                if let Err(e) = utxo.spend(db, transaction.signer()).await {
+                    Self::restore(&trx_collection, trx_backup).await;
+                    return Err(e);
+                }
+            }
+            match trx_collection.delete_one(doc! {"hash": &transaction.hash}).await {
+                Ok(_) => {
+                    trx_backup.push(transaction.clone());
+                }
+                Err(e) => {
+                    error!("Error deleting transaction: {}", e);
+                }
+            }
+        }
+
+        // Generate UTXOs of the block's outputs and then update the waiting validators
+        let applied = match self.outputs(db).await {
+            Ok(_) => Waiting::save(db, &changes.waiting).await,
+            Err(e) => Err(e),
+        };
+        match applied {
+            // Commitment is saved last, so a saved root means the block was applied completely
+            Ok(_) => changes.commitment.save(db).await.map(|_| self),
+            Err(e) => {
+                error!("Error applying block: {}", e);
+                // If applying the block fails, restore the transactions from the backup
+                Self::restore(&trx_collection, trx_backup).await;
+                Err(e)
+            }
+        }
+    }
+
+    // Generate UTXOs of the block's outputs and return stakes that their unbonding is finished
+    async fn outputs<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        // Generate new UTXOs for each unspent output in the coinbase transaction
+        for unspent in &self.body.coinbase.output.unspents {
+            UTXO::generate(
+                self.header.number,
+                &self.body.coinbase.hash,
+                &self.body.coinbase.output.hash,
+                unspent,
+                db,
+            )
+            .await?;
+        }
+
+        // Generate new UTXOs for each unspent output in the block's transactions
+        // Bond output of a stake transaction is locked as stake instead of being a UTXO
+        for trx in &self.body.transactions {
+            let locked = Stake::apply(trx, self.header.number, db).await?;
+            for unspent in trx.output.unspents.iter().skip(locked as usize) {
+                UTXO::generate(self.header.number, &trx.hash, &trx.output.hash, unspent, db).await?;
+            }
+        }
+
+        Stake::release(self.header.number, db).await
+    }
+
+    // Put transactions of a block that couldn't be applied back to the mempool
+    async fn restore(trx_collection: &Collection<Document>, trx_backup: Vec<Transaction>) {
+        for trx in trx_backup {
+            let trx_doc = to_document(&trx).unwrap();
+            if let Err(e) = trx_collection.insert_one(trx_doc).await {
+                error!("Error restoring transaction: {}", e);
+            }
+        }
+    }
+
+    // Check the block's transactions for spending an unspent more than once inside the block
+    // and check their inputs against the UTXO set at the block's parent.
+    // Inputs of a transaction that this relay admitted before were spent from the UTXO set
+    // at admission and are kept in the mempool with it.
+    async fn inputs_validation<'a>(
+        transactions: &[Transaction],
+        number: u64,
+        db: &'a Database,
+    ) -> Result<(), TransactionError<'a>> {
+        let mempool_collection: Collection<Document> = db.collection("transactions");
+        let mut spent: HashSet<&String> = HashSet::new();
+
+        if transactions.len() > CHAIN_PARAMS.max_block_size {
+            return Err(TransactionError::Invalid(
+                "Block has more transactions than max block size and Block rejected.",
+            ));
+        }
+
+        for transaction in transactions {
+            // Hashes, signature and values should be correct regardless of mempool presence
+            transaction.verify_in(Some(number))?;
+
+            for utxo in transaction.inputs() {
+                if !spent.insert(&utxo.unspent_hash) {
+                    return Err(TransactionError::Invalid("Block has a double spend and Block rejected."));
+                }
+            }
+
+            // Inputs of an admitted transaction were reserved at admission, so they must be the same ones
+            let filter = doc! {"hash": &transaction.hash};
+            match mempool_collection.find_one(filter).await {
+                Ok(Some(doc)) => {
+                    let reserved: Transaction = match from_document(doc) {
+                        Ok(reserved) => reserved,
+                        Err(_) => {
+                            return Err(TransactionError::Query(
+                                "Reading mempool transaction problem-(relay/practical/block 326)",
+                            ))
                         }
+                    };
+                    if reserved.inputs() != transaction.inputs() {
+                        return Err(TransactionError::Invalid(
+                            "Transaction inputs are not the ones reserved at its admission and Block rejected.",
+                        ));
                     }
-                } else {
-                    write_log(&format!("Transaction validation error: {}", trx_err.unwrap()));
-                    // If transaction validation fails, restore the transactions from the backup
-                    for trx in trx_backup {
-                        let trx_doc = to_document(&trx).unwrap();
-                        match trx_collection.insert_one(trx_doc).await {
-                            Ok(_) => {}
-                            Err(e) => {
-                                write_log(&format!("Error restoring transaction after validation failure: {}", e));
-                            }
+                }
+                Ok(None) => {
+                    Stake::unbond_validation(transaction, db).await?;
+                    for utxo in transaction.inputs() {
+                        match utxo.exists(db, &transaction.signature[0].key).await {
+                            Ok(true) => {}
+                            Ok(false) => return Err(TransactionError::Invalid("UTXO does not exist!")),
+                            Err(e) => return Err(TransactionError::Query(e)),
                         }
                     }
-                    Err(trx_err.unwrap())
                 }
-            } else {
-                let error_msg = "Block signature is wrong and Block rejected.";
-                write_log(error_msg);
-                Err(error_msg)
+                Err(_) => {
+                    return Err(TransactionError::Query(
+                        "Querying mempool transaction problem-(relay/practical/block 348)",
+                    ))
+                }
             }
-        } else {
-            let error_msg = "Block validation problem!, previous hash doesn't match and Block rejected.";
-            write_log(error_msg);
-            Err(error_msg)
         }
+
+        Ok(())
     }
 }
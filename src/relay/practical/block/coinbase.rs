@@ -1,19 +1,30 @@
-use std::str::FromStr;
+use std::collections::HashMap;
 
+use mongodb::{
+    bson::{doc, from_document, Document},
+    Collection, Database,
+};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
+use sp_core::ed25519::Public;
+use tracing::warn;
 
 use crate::relay::{
-    practical::transaction::{Output, Transaction},
-    tools::MerkelRoot,
+    practical::{
+        chain_params::CHAIN_PARAMS,
+        relay::RelayWallet,
+        transaction::{Output, Transaction},
+        validator::Validator,
+    },
+    tools::{merkel::MerkelRoot, HashMaker},
 };
 
-use super::{block::Block, reward::Reward};
+use super::{block::Block, header::Header, reward::Reward};
 
 //fees are sum of transactions fees
-//relay's fee is 10% of fees
-//validator fee is 90% of fees
+//relay's fee is relay fee share of fees (10% on mainnet)
+//validator fee is rest of fees
 //reward is only for validator
 //merkel is merkel root of transactions
 //size is number of transactions
@@ -35,13 +46,46 @@ pub struct Coinbase {
     validator_fee: Decimal,
 }
 
+//reasons that a coinbase can be rejected for
+#[derive(Debug, PartialEq)]
+pub enum CoinbaseError<'a> {
+    Reward,
+    Merkel,
+    Fees,
+    Hash,
+    Validator,
+    Value,
+    Outputs,
+    //wallet of block's relay isn't known to this relay, so its fee can't be checked
+    RelayWallet,
+    Query(&'a str),
+}
+
+impl<'a> CoinbaseError<'a> {
+    pub fn message(&self) -> &'a str {
+        match self {
+            Self::Reward => "Recieved block's reward in coinbase is incorrect!",
+            Self::Merkel => "Merkel root of coinbase is wrong!",
+            Self::Fees => "fees of coinbase transaction is wrong!",
+            Self::Hash => "Coinbase's hash is wrong!",
+            Self::Validator => "Coinbase's validator is not a known validator!",
+            Self::Value => "Coinbase has an output without value!",
+            Self::Outputs => "Coinbase's outputs don't match reward and fees of validator and relay!",
+            Self::RelayWallet => "Wallet of block's relay is unknown and relay fee can't be checked!",
+            Self::Query(e) => e,
+        }
+    }
+}
+
 impl Coinbase {
     //validating coinbase trx in a block that recieve
     pub async fn validation<'a>(
         &self,
         last_block: &mut Vec<Block>,
         transactions: &Vec<Transaction>,
-    ) -> Result<(), &'a str> {
+        header: &Header,
+        db: &'a Database,
+    ) -> Result<(), CoinbaseError<'a>> {
         let reward = Reward::calculate(last_block);
         if self.reward == reward {
             let mut merkel = "First".to_string();
@@ -52,14 +96,18 @@ impl Coinbase {
                 for t in transactions {
                     trx_hashes.push(&t.hash);
                 }
-                merkel = MerkelRoot::make(trx_hashes).first().unwrap().clone();
+                merkel = if CHAIN_PARAMS.upgraded(header.number) {
+                    MerkelRoot::make(trx_hashes).remove(0)
+                } else {
+                    MerkelRoot::legacy(trx_hashes).remove(0)
+                };
             }
 
             //check merkel root that maked with coinbase merkel root to validation
             if merkel == self.merkel {
                 //calculate fees
                 let fees: Decimal = transactions.iter().map(|trx| trx.fee).sum();
-                let relay_fee = fees * Decimal::from_str("0.10").unwrap();
+                let relay_fee = fees * CHAIN_PARAMS.relay_fee_share;
                 let validator_fee = fees - relay_fee;
 
                 //if fees was correct check outputs of coinbase for validate relay fee
@@ -67,31 +115,155 @@ impl Coinbase {
                     && relay_fee == self.relay_fee
                     && validator_fee == self.validator_fee
                 {
-                    // if relay's fee was greater that 0 then check outputs of coinbase to see relay's fee
-                    //if there is no any output for relay's fee coinbase will be rejected
-                    if relay_fee > Decimal::from_str("0.0").unwrap() {
-                        let mut relay_fee_check: Option<bool> = None;
-                        for unspent in &self.output.unspents {
-                            if unspent.data.value == relay_fee {
-                                relay_fee_check.get_or_insert(true);
-                            }
-                        }
-                        if relay_fee_check.unwrap() {
-                            Ok(())
-                        } else {
-                            Err("Coinbase's relay fee is wrong!")
-                        }
-                    } else {
-                        Ok(())
-                    }
+                    //check hashes of coinbase and that its outputs pay exactly reward and fees to validator and relay
+                    self.outputs_validation(header, db).await
                 } else {
-                    Err("fees of coinbase transaction is wrong!")
+                    Err(CoinbaseError::Fees)
                 }
             } else {
-                Err("Merkel root of coinbase is wrong!")
+                Err(CoinbaseError::Merkel)
             }
         } else {
-            Err("Recieved block's reward in coinbase is incorrect!")
+            Err(CoinbaseError::Reward)
+        }
+    }
+
+    //check hash of coinbase and its output, then check that outputs pay reward and validator's fee
+    //to the block's validator wallet and relay's fee to the block's relay wallet without any extra coins
+    async fn outputs_validation<'a>(
+        &self,
+        header: &Header,
+        db: &'a Database,
+    ) -> Result<(), CoinbaseError<'a>> {
+        let unspents_str = serde_json::to_string(&self.output.unspents).unwrap();
+        let output_str = serde_json::to_string(&self.output).unwrap();
+        if HashMaker::generate(&unspents_str) != self.output.hash
+            || HashMaker::generate(&output_str) != self.hash
+            || self.output.number != self.output.unspents.len()
+        {
+            return Err(CoinbaseError::Hash);
+        }
+
+        //get wallet of block's validator
+        let collection: Collection<Document> = db.collection("validators");
+        let validator_wallet = match collection
+            .find_one(doc! {"peerid": header.validator.to_string()})
+            .await
+        {
+            Ok(Some(doc)) => match from_document::<Validator>(doc) {
+                Ok(validator) => validator.wallet,
+                Err(_) => return Err(CoinbaseError::Query("Reading validator of coinbase problem-(relay/practical/coinbase 155)")),
+            },
+            Ok(None) => return Err(CoinbaseError::Validator),
+            Err(_) => return Err(CoinbaseError::Query("Querying validator of coinbase problem-(relay/practical/coinbase 158)")),
+        };
+
+        //sum of values that each wallet should recieve
+        let mut expected: HashMap<Public, Decimal> = HashMap::new();
+        *expected.entry(validator_wallet).or_default() += self.reward + self.validator_fee;
+        if self.relay_fee > Decimal::ZERO {
+            match RelayWallet::get(db, &header.relay).await {
+                Ok(Some(relay_wallet)) => {
+                    *expected.entry(relay_wallet).or_default() += self.relay_fee;
+                }
+                //relay didn't announce its wallet to this relay yet, validator can't be blamed for it
+                Ok(None) => {
+                    warn!("Wallet of relay {} is unknown, block is rejected", header.relay);
+                    return Err(CoinbaseError::RelayWallet);
+                }
+                Err(e) => return Err(CoinbaseError::Query(e)),
+            }
         }
+
+        //sum of values that each wallet recieved in coinbase outputs
+        let mut paid: HashMap<Public, Decimal> = HashMap::new();
+        for unspent in &self.output.unspents {
+            if unspent.data.value <= Decimal::ZERO {
+                return Err(CoinbaseError::Value);
+            }
+            *paid.entry(unspent.data.wallet).or_default() += unspent.data.value;
+        }
+
+        if paid == expected {
+            Ok(())
+        } else {
+            Err(CoinbaseError::Outputs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+    use mongodb::bson::to_document;
+    use serde_json::json;
+    use sp_core::{ed25519, Pair};
+
+    use crate::relay::practical::{db::Mongodb, transaction::Unspent};
+
+    use super::*;
+
+    //coinbase that pays reward and validator fee to validator and relay fee to relay
+    fn coinbase(validator: Public, relay: Public) -> Coinbase {
+        let output = Output::new(vec![
+            Unspent::new(validator, Decimal::new(59, 0)),
+            Unspent::new(relay, Decimal::new(1, 0)),
+        ]);
+        Coinbase {
+            hash: HashMaker::generate(&serde_json::to_string(&output).unwrap()),
+            size: 1,
+            merkel: "First".to_string(),
+            reward: Decimal::new(50, 0),
+            output,
+            fees: Decimal::new(10, 0),
+            relay_fee: Decimal::new(1, 0),
+            validator_fee: Decimal::new(9, 0),
+        }
+    }
+
+    fn header(validator: PeerId, relay: PeerId, pair: &ed25519::Pair) -> Header {
+        serde_json::from_value(json!({
+            "number": 1,
+            "hash": "hash-1",
+            "previous": "hash-0",
+            "validator": validator,
+            "relay": relay,
+            "merkel": "First",
+            "signature": {"signatgure": pair.sign(b""), "key": pair.public()},
+            "date": "2024-01-01 00:00:00 UTC",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn rejects_relay_fee_of_unknown_relay_wallet() {
+        let db = Mongodb::connect_to("Centichain_test_coinbase").await.unwrap();
+        db.drop().await.unwrap();
+
+        let pair = ed25519::Pair::generate().0;
+        let relay_wallet = ed25519::Pair::generate().0.public();
+        let header = header(PeerId::random(), PeerId::random(), &pair);
+        let validator = Validator {
+            peerid: header.validator,
+            relay: header.relay,
+            wallet: pair.public(),
+            waiting: 0,
+        };
+        let collection: Collection<Document> = db.collection("validators");
+        collection.insert_one(to_document(&validator).unwrap()).await.unwrap();
+
+        let coinbase = coinbase(pair.public(), relay_wallet);
+        let result = coinbase.outputs_validation(&header, &db).await;
+        assert_eq!(result, Err(CoinbaseError::RelayWallet));
+
+        RelayWallet::new(header.relay, relay_wallet).save(&db).await.unwrap();
+        assert_eq!(coinbase.outputs_validation(&header, &db).await, Ok(()));
+
+        let other = ed25519::Pair::generate().0.public();
+        RelayWallet::new(header.relay, other).save(&db).await.unwrap();
+        let result = coinbase.outputs_validation(&header, &db).await;
+        assert_eq!(result, Err(CoinbaseError::Outputs));
+        db.drop().await.unwrap();
     }
 }
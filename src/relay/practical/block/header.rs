@@ -1,6 +1,24 @@
+use chrono::{Duration, NaiveDateTime, Utc};
 use libp2p::PeerId;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    Collection, Database,
+};
 use serde::{Deserialize, Serialize};
-use sp_core::ed25519::{Public, Signature};
+use sp_core::{
+    ed25519::{Public, Signature},
+    Pair,
+};
+
+use crate::relay::{
+    practical::{chain_params::CHAIN_PARAMS, validator::Validator},
+    tools::merkel::MerkelProof,
+};
+
+use super::{block::Block, coinbase::Coinbase};
+
+// Maximum seconds that a block's date can be ahead of this relay's clock.
+const MAX_FUTURE_SECS: i64 = 120;
 
 // Define the structure of a block's header with signature.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -11,6 +29,9 @@ pub struct Header {
     pub validator: PeerId,
     pub relay: PeerId,
     merkel: String,
+    // Commitment of utxo set after the block, blocks of older validators don't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utxo_root: Option<String>,
     pub signature: Sign,
     date: String,
 }
@@ -20,4 +41,354 @@ pub struct Header {
 pub struct Sign {
     pub signatgure: Signature,
     pub key: Public,
-}
\ No newline at end of file
+}
+
+// Define the reasons that a header can be rejected for.
+#[derive(Debug, PartialEq)]
+pub enum HeaderError {
+    Number,
+    Hash,
+    Merkel,
+    Date,
+    Relay,
+    Signer,
+    Query,
+}
+
+impl HeaderError {
+    // Return a message of the error to log it and reject the block with it
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Number => "Block number is not the next number of the last block and Block rejected.",
+            Self::Hash => "Block hash doesn't match the block's content and Block rejected.",
+            Self::Merkel => "Block merkel root doesn't match the coinbase merkel root and Block rejected.",
+            Self::Date => "Block date is not valid or is older than the last block and Block rejected.",
+            Self::Relay => "Block relay is not a known relay and Block rejected.",
+            Self::Signer => "Block signer is not the registered wallet of its validator and Block rejected.",
+            Self::Query => "Querying validators for header validation problem-(relay/practical/header 68)",
+        }
+    }
+}
+
+impl Header {
+    // Payload that validator signs for a block, it binds the block's content to its height, parent,
+    // merkel root and utxo root, so light clients can check transactions of a header without its body
+    pub fn payload(&self, hash: &str) -> String {
+        Self::signing_payload(
+            self.number,
+            &self.previous,
+            &self.merkel,
+            self.utxo_root.as_deref(),
+            hash,
+        )
+    }
+
+    pub fn signing_payload(
+        number: u64,
+        previous: &str,
+        merkel: &str,
+        utxo_root: Option<&str>,
+        hash: &str,
+    ) -> String {
+        // Blocks below upgrade height are signed over their hash only
+        if !CHAIN_PARAMS.upgraded(number) {
+            return hash.to_string();
+        }
+        let mut payload = format!("{}:{}:{}", number, previous, merkel);
+        if let Some(utxo_root) = utxo_root {
+            payload.push_str(&format!(":{}", utxo_root));
+        }
+        CHAIN_PARAMS.signing_payload(&format!("{}:{}", payload, hash))
+    }
+
+    // Merkel root of block's transactions
+    pub fn merkel(&self) -> &str {
+        &self.merkel
+    }
+
+    // Check signature of header over its own hash
+    pub fn verify_sign(&self) -> bool {
+        sp_core::ed25519::Pair::verify(
+            &self.signature.signatgure,
+            self.payload(&self.hash),
+            &self.signature.key,
+        )
+    }
+
+    // Date of block in "YYYY-MM-DD HH:MM:SS UTC" format
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    // Unix time of block's date
+    pub fn timestamp(&self) -> Option<i64> {
+        Self::parse_date(&self.date).map(|date| date.and_utc().timestamp())
+    }
+
+    // Check that a transaction is in this block with its merkel proof
+    // merkel roots of blocks below upgrade height don't have proofs
+    pub fn includes(&self, proof: &MerkelProof) -> bool {
+        CHAIN_PARAMS.upgraded(self.number) && proof.verify(&self.merkel)
+    }
+
+    // Parse the date of header that is in "YYYY-MM-DD HH:MM:SS UTC" format
+    fn parse_date(date: &str) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(date.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S").ok()
+    }
+
+    // Validate header's fields against the last block, the block's content, known relays
+    // and the registered wallet of the header's validator
+    pub async fn validation(
+        &self,
+        last_block: &[Block],
+        content_hash: &String,
+        coinbase: &Coinbase,
+        db: &Database,
+    ) -> Result<(), HeaderError> {
+        self.fields_validation(last_block, content_hash, &coinbase.merkel)?;
+
+        // Relay should be a relay that validators are connected with
+        let collection: Collection<Document> = db.collection("validators");
+        match collection
+            .find_one(doc! {"relay": self.relay.to_string()})
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(HeaderError::Relay),
+            Err(_) => return Err(HeaderError::Query),
+        }
+
+        // Header should be signed by the wallet that its validator registered with
+        match collection
+            .find_one(doc! {"peerid": self.validator.to_string()})
+            .await
+        {
+            Ok(doc) => self.signer_validation(doc.and_then(|doc| from_document(doc).ok())),
+            Err(_) => Err(HeaderError::Query),
+        }
+    }
+
+    // Validate fields of header that don't need database
+    fn fields_validation(
+        &self,
+        last_block: &[Block],
+        content_hash: &String,
+        coinbase_merkel: &str,
+    ) -> Result<(), HeaderError> {
+        // Number should be the next number of the last block, genesis block number is 1
+        let expected_number = match last_block.first() {
+            Some(last) => last.header.number + 1,
+            None => 1,
+        };
+        if self.number != expected_number {
+            return Err(HeaderError::Number);
+        }
+
+        // Hash should be made from the block's content
+        if &self.hash != content_hash {
+            return Err(HeaderError::Hash);
+        }
+
+        // Merkel root should be same as the coinbase merkel root
+        if self.merkel != coinbase_merkel {
+            return Err(HeaderError::Merkel);
+        }
+
+        // Date should not be in the future and should not be older than the last block
+        match Self::parse_date(&self.date) {
+            Some(date) => {
+                if date > Utc::now().naive_utc() + Duration::seconds(MAX_FUTURE_SECS) {
+                    return Err(HeaderError::Date);
+                }
+                if let Some(last) = last_block.first() {
+                    match Self::parse_date(&last.header.date) {
+                        Some(last_date) if date >= last_date => {}
+                        _ => return Err(HeaderError::Date),
+                    }
+                }
+                Ok(())
+            }
+            None => Err(HeaderError::Date),
+        }
+    }
+
+    // Signer of header should be the registered wallet of its validator
+    fn signer_validation(&self, validator: Option<Validator>) -> Result<(), HeaderError> {
+        match validator {
+            Some(validator)
+                if validator.peerid == self.validator && validator.wallet == self.signature.key =>
+            {
+                Ok(())
+            }
+            _ => Err(HeaderError::Signer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration as StdDuration;
+
+    use mongodb::{bson::to_document, options::ClientOptions, Client};
+    use serde_json::json;
+    use sp_core::ed25519;
+
+    use crate::relay::practical::{block::block::Body, db::Mongodb};
+
+    use super::*;
+
+    fn header(pair: &ed25519::Pair, number: u64, date: &str) -> Header {
+        let hash = format!("hash-{}", number);
+        Header {
+            number,
+            hash: hash.clone(),
+            previous: format!("hash-{}", number - 1),
+            validator: PeerId::random(),
+            relay: PeerId::random(),
+            merkel: "First".to_string(),
+            utxo_root: None,
+            signature: Sign {
+                signatgure: pair.sign(hash.as_bytes()),
+                key: pair.public(),
+            },
+            date: date.to_string(),
+        }
+    }
+
+    fn block(header: Header) -> Block {
+        let coinbase: Coinbase = serde_json::from_value(json!({
+            "hash": "coinbase",
+            "size": 0,
+            "merkel": "First",
+            "reward": "50",
+            "output": {"hash": "output", "number": 0, "unspents": []},
+            "fees": "0",
+            "relay_fee": "0",
+            "validator_fee": "0",
+        }))
+        .unwrap();
+        Block {
+            header,
+            body: Body {
+                coinbase,
+                transactions: Vec::new(),
+            },
+        }
+    }
+
+    fn validator(header: &Header, wallet: Public) -> Validator {
+        Validator {
+            peerid: header.validator,
+            relay: header.relay,
+            wallet,
+            waiting: 0,
+        }
+    }
+
+    #[test]
+    fn accepts_next_header() {
+        let pair = ed25519::Pair::generate().0;
+        let last = block(header(&pair, 1, "2024-01-01 00:00:00 UTC"));
+        let next = header(&pair, 2, "2024-01-01 00:01:00 UTC");
+        assert_eq!(next.fields_validation(&[last], &next.hash.clone(), "First"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_wrong_number() {
+        let pair = ed25519::Pair::generate().0;
+        let last = block(header(&pair, 1, "2024-01-01 00:00:00 UTC"));
+        let next = header(&pair, 3, "2024-01-01 00:01:00 UTC");
+        let result = next.fields_validation(&[last], &next.hash.clone(), "First");
+        assert_eq!(result, Err(HeaderError::Number));
+    }
+
+    #[test]
+    fn rejects_wrong_hash() {
+        let pair = ed25519::Pair::generate().0;
+        let next = header(&pair, 1, "2024-01-01 00:01:00 UTC");
+        let result = next.fields_validation(&[], &"other".to_string(), "First");
+        assert_eq!(result, Err(HeaderError::Hash));
+    }
+
+    #[test]
+    fn rejects_wrong_merkel() {
+        let pair = ed25519::Pair::generate().0;
+        let next = header(&pair, 1, "2024-01-01 00:01:00 UTC");
+        let result = next.fields_validation(&[], &next.hash.clone(), "other");
+        assert_eq!(result, Err(HeaderError::Merkel));
+    }
+
+    #[test]
+    fn rejects_wrong_date() {
+        let pair = ed25519::Pair::generate().0;
+        let last = block(header(&pair, 1, "2024-01-01 00:01:00 UTC"));
+
+        let older = header(&pair, 2, "2024-01-01 00:00:00 UTC");
+        let result = older.fields_validation(std::slice::from_ref(&last), &older.hash.clone(), "First");
+        assert_eq!(result, Err(HeaderError::Date));
+
+        let future = (Utc::now() + Duration::seconds(MAX_FUTURE_SECS * 2)).format("%Y-%m-%d %H:%M:%S UTC");
+        let future = header(&pair, 2, &future.to_string());
+        let result = future.fields_validation(std::slice::from_ref(&last), &future.hash.clone(), "First");
+        assert_eq!(result, Err(HeaderError::Date));
+
+        let unparsable = header(&pair, 2, "yesterday");
+        let result = unparsable.fields_validation(&[last], &unparsable.hash.clone(), "First");
+        assert_eq!(result, Err(HeaderError::Date));
+    }
+
+    #[test]
+    fn rejects_wrong_signer() {
+        let pair = ed25519::Pair::generate().0;
+        let other = ed25519::Pair::generate().0;
+        let next = header(&pair, 1, "2024-01-01 00:01:00 UTC");
+
+        assert_eq!(next.signer_validation(Some(validator(&next, pair.public()))), Ok(()));
+        assert_eq!(
+            next.signer_validation(Some(validator(&next, other.public()))),
+            Err(HeaderError::Signer)
+        );
+        assert_eq!(next.signer_validation(None), Err(HeaderError::Signer));
+    }
+
+    // Relay and signer are checked against validators in database
+    #[tokio::test]
+    async fn rejects_header_when_database_fails() {
+        let mut options = ClientOptions::parse("mongodb://127.0.0.1:1").await.unwrap();
+        options.server_selection_timeout = Some(StdDuration::from_millis(100));
+        let db = Client::with_options(options).unwrap().database("Centichain_test");
+
+        let pair = ed25519::Pair::generate().0;
+        let next = block(header(&pair, 1, "2024-01-01 00:01:00 UTC"));
+        let result = next
+            .header
+            .validation(&[], &next.header.hash, &next.body.coinbase, &db)
+            .await;
+        assert_eq!(result, Err(HeaderError::Query));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn rejects_unknown_relay() {
+        let db = Mongodb::connect_to("Centichain_test_header").await.unwrap();
+        db.drop().await.unwrap();
+
+        let pair = ed25519::Pair::generate().0;
+        let next = block(header(&pair, 1, "2024-01-01 00:01:00 UTC"));
+        let result = next
+            .header
+            .validation(&[], &next.header.hash, &next.body.coinbase, &db)
+            .await;
+        assert_eq!(result, Err(HeaderError::Relay));
+
+        let collection: Collection<Document> = db.collection("validators");
+        let registered = validator(&next.header, pair.public());
+        collection.insert_one(to_document(&registered).unwrap()).await.unwrap();
+        let result = next
+            .header
+            .validation(&[], &next.header.hash, &next.body.coinbase, &db)
+            .await;
+        assert_eq!(result, Ok(()));
+        db.drop().await.unwrap();
+    }
+}
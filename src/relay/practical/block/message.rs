@@ -1,17 +1,22 @@
-use libp2p::{PeerId, Swarm};
-use mongodb::{
-    bson::{to_document, Document},
-    Collection, Database,
-};
+use libp2p::{identity::Keypair, PeerId, Swarm};
+use mongodb::Database;
 use serde::{Deserialize, Serialize};
+use sp_core::ed25519::Public;
+use tracing::{debug, error, instrument, warn};
 
 use crate::relay::{
     events::connections::ConnectionsHandler,
-    practical::{leader::Leader, reciept::Reciept, swarm::CentichainBehaviour},
-    tools::{create_log::write_log, syncer::Sync},
+    practical::{
+        earnings::Earning, evidence::Evidence, finality::Attestation, leader::Leader,
+        swarm::CentichainBehaviour,
+    },
+    tools::{metrics::METRICS, syncer::Sync, wrongdoer::WrongDoer},
 };
 
-use super::block::Block;
+use super::{
+    block::{Block, BlockError},
+    pending::PendingBlock,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BlockMessage {
@@ -21,6 +26,8 @@ pub struct BlockMessage {
 
 impl BlockMessage {
     // Handle received block messages
+    // Returns true if the block is accepted or kept for after syncing, so it can be propagated
+    #[instrument(name = "block", skip_all, fields(number = self.block.header.number, hash = %self.block.header.hash))]
     pub async fn handle<'a>(
         &self,
         swarm: &mut Swarm<CentichainBehaviour>,
@@ -30,73 +37,79 @@ impl BlockMessage {
         last_block: &mut Vec<Block>,
         leader: &mut Leader,
         connections_handler: &mut ConnectionsHandler,
-    ) -> Result<(), &'a str> {
-        write_log("handle block message");
+        wallet: &Public,
+        keypair: &Keypair,
+    ) -> Result<bool, &'a str> {
+        debug!("handle block message");
+        // Keep signed header of block and punish its validator if it signed another block at this height
+        if let Some(evidence) = Evidence::observe(db, &self.block.header).await? {
+            METRICS.block_rejected("Equivocation");
+            return evidence
+                .handle(db, leader, connections_handler, swarm)
+                .await
+                .map(|_| false);
+        }
         // Check if the current node is the leader
         if leader.peerid.is_none() || self.block.header.validator == leader.peerid.unwrap() {
-            write_log("leader checked");
+            debug!("leader checked");
             match sync_state {
                 // If the current relay node is synced, proceed with block validation
-                Sync::Synced => match self.block.validation(last_block, db).await {
-                    // If the block is valid, insert it into the blockchain
-                    Ok(block) => {
-                        let collection: Collection<Document> = db.collection("Blocks");
-                        let doc = to_document(&block).unwrap();
-                        match collection.insert_one(doc).await {
-                            // If block insertion succeeds, insert receipts for coinbase output and transactions
-                            Ok(_) => {
-                                let mut is_err = None;
-                                // Insert receipt for coinbase
-                                match Reciept::insertion(Some(self.block.header.number), None, Some(&self.block.body.coinbase), db).await {
-                                    Ok(_) => {
-                                        // Confirm receipts for all transactions in the block
-                                        for transaction in self.block.body.transactions.clone() {
-                                            match Reciept::confirmation(db, &transaction.hash, &self.block.header.number).await {
-                                                Ok(_) => {
-                                                }
-                                                Err(e) => {
-                                                    is_err.get_or_insert(e);
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {is_err.get_or_insert(e);}
-                                }
-                                
-                                match is_err {
-                                    None => {
-                                        // Update last block and leader if all receipts are inserted successfully
-                                        last_block.clear();
-                                        last_block.push(block.clone());
-                                        Ok(leader.update(Some(self.next_leader)))
-                                    },
-                                    Some(e) => Err(e)
-                                }
+                Sync::Synced => {
+                    // The block is kept as pending from its first change of state until it is committed,
+                    // so a stop in the middle of it is finished at the next start
+                    match PendingBlock::accept(db, &self.block, last_block).await {
+                        // If the block is valid, it is inserted into the blockchain with its receipts
+                        Ok(_) => {
+                            METRICS.block_accepted();
+                            // Update last block and leader
+                            last_block.clear();
+                            last_block.push(self.block.clone());
+                            leader.update(Some(self.next_leader));
+                            // Index earnings of this relay's wallet, a failed indexing is caught up with the next block
+                            if let Err(e) = Earning::index(db, wallet).await {
+                                warn!("{}", e);
                             }
-                            Err(_) => Err("Error while inserting new block to database-(generator/block/message 67)")
+                            // Attest to the block that has enough confirmations now
+                            Attestation::attest(db, swarm, keypair, self.block.header.number)
+                                .await
+                                .map(|_| true)
                         }
-                    }
-                    // If block validation fails, remove the validator and start voting for a new leader
-                    Err(e) => {
-                        match connections_handler.remove(db, self.block.header.validator, swarm).await {
-                            Ok(_) => {
-                                write_log(e);
-                                leader.start_voting(db, connections_handler, swarm).await
+                        // If the validator broke a rule with its block, slash and remove it and start voting for a new leader
+                        Err(BlockError::Fault(e)) => {
+                            METRICS.block_rejected(e);
+                            WrongDoer::new(self.block.header.validator, e).slash(db).await?;
+                            match connections_handler.remove(db, self.block.header.validator, swarm).await {
+                                Ok(_) => {
+                                    error!("{}", e);
+                                    leader.start_voting(db, connections_handler, swarm).await.map(|_| false)
+                                }
+                                Err(e) => Err(e)
                             }
-                            Err(e) => Err(e)
                         }
+                        // If the validator can't be blamed for the block, only reject it
+                        Err(BlockError::Rejected(e)) => {
+                            METRICS.block_rejected(e);
+                            warn!("{}", e);
+                            Ok(false)
+                        }
+                        // If this relay couldn't check or apply the block, stop and finish it at restart if it is pending
+                        Err(BlockError::Internal(e)) => Err(e),
                     }
-                },
+                }
                 // If the current relay node is not synced, store the received block message for later processing
-                Sync::NotSynced => Ok(recvied_blocks.push(self.clone())),
+                Sync::NotSynced => {
+                    recvied_blocks.push(self.clone());
+                    Ok(true)
+                }
             }
         } else {
-            write_log("leader is not correct");
+            warn!("leader is not correct");
+            METRICS.block_rejected("Block is not from the leader");
             // If the block is from an unexpected validator, remove it from the network
             connections_handler
                 .remove(db, self.block.header.validator, swarm)
                 .await
+                .map(|_| false)
         }
     }
 }
@@ -2,4 +2,5 @@ pub mod block;
 pub mod coinbase;
 pub mod header;
 pub mod reward;
-pub mod message;
\ No newline at end of file
+pub mod message;
+pub mod pending;
@@ -0,0 +1,280 @@
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::relay::{
+    practical::{commitment::UtxoCommitment, reciept::Reciept},
+};
+
+use super::block::{Block, BlockError, Changes};
+
+//applying a block changes many collections one after another, so the block is kept here
+//from its first change of state until it and its reciepts are inserted
+//a relay that stopped in the middle finds the block here at start and finishes it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingBlock {
+    pub block: Block,
+    //pending blocks of older relays don't have their changes
+    #[serde(default)]
+    pub changes: Option<Changes>,
+}
+
+impl PendingBlock {
+    //check a block, keep it as pending only before changing state by it, then apply and insert it
+    //so a block that failed before changing anything doesn't remain pending
+    pub async fn accept<'a>(
+        db: &'a Database,
+        block: &Block,
+        last_block: &mut Vec<Block>,
+    ) -> Result<(), BlockError<'a>> {
+        let changes = block.check(last_block, db).await?;
+        Self::start(db, block, &changes).await.map_err(BlockError::Internal)?;
+        block.apply(&changes, db).await.map_err(BlockError::Internal)?;
+        Self::commit(db, block).await.map_err(BlockError::Internal)
+    }
+
+    async fn start<'a>(db: &'a Database, block: &Block, changes: &Changes) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("pending_block");
+        let pending = Self {
+            block: block.clone(),
+            changes: Some(changes.clone()),
+        };
+        match collection.delete_many(doc! {}).await {
+            Ok(_) => match collection.insert_one(to_document(&pending).unwrap()).await {
+                Ok(_) => Ok(()),
+                Err(_) => Err("Saving pending block problem-(relay/practical/block/pending 48)"),
+            },
+            Err(_) => Err("Clearing pending block problem-(relay/practical/block/pending 50)"),
+        }
+    }
+
+    pub async fn finish(db: &Database) -> Result<(), &str> {
+        let collection: Collection<Document> = db.collection("pending_block");
+        match collection.delete_many(doc! {}).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Clearing pending block problem-(relay/practical/block/pending 58)"),
+        }
+    }
+
+    //insert a validated block with its reciepts and clear the pending block
+    pub async fn commit<'a>(db: &'a Database, block: &Block) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("Blocks");
+        match collection.insert_one(to_document(block).unwrap()).await {
+            Ok(_) => {
+                Self::reciepts(db, block).await?;
+                Self::finish(db).await
+            }
+            Err(_) => Err("Error while inserting new block to database-(relay/practical/block/pending 70)"),
+        }
+    }
+
+    //coinbase reciepts are inserted once and reciepts of transactions are confirmed
+    //so it can run again for a block that its reciepts were half made
+    async fn reciepts<'a>(db: &'a Database, block: &Block) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("reciepts");
+        let number = block.header.number;
+        match collection
+            .count_documents(doc! {"hash": &block.body.coinbase.hash})
+            .await
+        {
+            Ok(0) => Reciept::insertion(Some(number), None, Some(&block.body.coinbase), db).await?,
+            Ok(_) => {}
+            Err(_) => return Err("Querying reciepts problem-(relay/practical/block/pending 85)"),
+        }
+        for transaction in &block.body.transactions {
+            Reciept::confirmation(db, &transaction.hash, &number).await?;
+        }
+        Ok(())
+    }
+
+    //finish the block that relay was applying when it stopped
+    //utxo root of a block is saved at the end of applying it, so if it exists only inserting remains
+    //else the block is applied again with its changes that were made before changing anything
+    pub async fn recover(db: &Database) -> Result<(), &str> {
+        let collection: Collection<Document> = db.collection("pending_block");
+        let pending: Self = match collection.find_one(doc! {}).await {
+            Ok(Some(doc)) => match from_document(doc) {
+                Ok(pending) => pending,
+                Err(_) => return Err("Reading pending block problem-(relay/practical/block/pending 101)"),
+            },
+            Ok(None) => return Ok(()),
+            Err(_) => return Err("Querying pending block problem-(relay/practical/block/pending 104)"),
+        };
+        let number = pending.block.header.number;
+
+        let blocks: Collection<Document> = db.collection("Blocks");
+        let filter = doc! {"header.number": number as i64, "header.hash": &pending.block.header.hash};
+        match blocks.find_one(filter).await {
+            Ok(Some(_)) => {
+                Self::reciepts(db, &pending.block).await?;
+                Self::finish(db).await?;
+            }
+            Ok(None) => match (UtxoCommitment::get(db, number).await?, &pending.changes) {
+                (Some(_), _) => Self::commit(db, &pending.block).await?,
+                (None, Some(changes)) => {
+                    pending.block.apply(changes, db).await?;
+                    Self::commit(db, &pending.block).await?
+                }
+                (None, None) => {
+                    return Err("Relay stopped in the middle of applying a block of an older version, state must be rebuilt with `relay-node reindex --promote`-(relay/practical/block/pending 122)")
+                }
+            },
+            Err(_) => return Err("Querying blocks problem-(relay/practical/block/pending 125)"),
+        }
+        info!("Pending block {} recovered.", number);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use libp2p::PeerId;
+    use mongodb::{options::ClientOptions, Client};
+    use serde_json::json;
+    use sp_core::{ed25519, Pair};
+
+    use crate::relay::{
+        practical::{block::block::Body, db::Mongodb, validator::Validator},
+        tools::HashMaker,
+    };
+
+    use super::*;
+
+    //signed block of a registered validator at number 2 with one coinbase output
+    fn block(pair: &ed25519::Pair) -> Block {
+        let body: Body = serde_json::from_value(json!({
+            "coinbase": {
+                "hash": "coinbase",
+                "size": 0,
+                "merkel": "First",
+                "reward": "50",
+                "output": {"hash": "output", "number": 1, "unspents": [{
+                    "hash": "unspent",
+                    "data": {"wallet": pair.public(), "salt": 0, "value": "50"},
+                }]},
+                "fees": "0",
+                "relay_fee": "0",
+                "validator_fee": "0",
+            },
+            "transactions": [],
+        }))
+        .unwrap();
+        let hash = HashMaker::generate(&serde_json::to_string(&body).unwrap());
+        let mut block: Block = serde_json::from_value(json!({
+            "header": {
+                "number": 2,
+                "hash": hash,
+                "previous": "hash-1",
+                "validator": PeerId::random(),
+                "relay": PeerId::random(),
+                "merkel": "First",
+                "signature": {"signatgure": pair.sign(b""), "key": pair.public()},
+                "date": "2024-01-01 00:01:00 UTC",
+            },
+            "body": body,
+        }))
+        .unwrap();
+        block.header.signature.signatgure = pair.sign(block.header.payload(&hash).as_bytes());
+        block
+    }
+
+    //parent of the block one minute before it
+    fn parent(block: &Block) -> Block {
+        let mut header = serde_json::to_value(&block.header).unwrap();
+        header["number"] = json!(1);
+        header["hash"] = json!(block.header.previous);
+        header["date"] = json!("2024-01-01 00:00:00 UTC");
+        Block {
+            header: serde_json::from_value(header).unwrap(),
+            body: block.body.clone(),
+        }
+    }
+
+    async fn register(db: &Database, block: &Block) {
+        let validator = Validator {
+            peerid: block.header.validator,
+            relay: block.header.relay,
+            wallet: block.header.signature.key,
+            waiting: 0,
+        };
+        let collection: Collection<Document> = db.collection("validators");
+        collection.insert_one(to_document(&validator).unwrap()).await.unwrap();
+    }
+
+    async fn count(db: &Database, name: &str) -> u64 {
+        let collection: Collection<Document> = db.collection(name);
+        collection.count_documents(doc! {}).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_block_when_database_fails() {
+        let mut options = ClientOptions::parse("mongodb://127.0.0.1:1").await.unwrap();
+        options.server_selection_timeout = Some(Duration::from_millis(100));
+        let db = Client::with_options(options).unwrap().database("Centichain_test");
+
+        let pair = ed25519::Pair::generate().0;
+        let block = block(&pair);
+        let result = PendingBlock::accept(&db, &block, &mut Vec::new()).await;
+        assert!(matches!(result, Err(BlockError::Internal(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn failed_block_before_any_write_is_not_pending() {
+        let db = Mongodb::connect_to("Centichain_test_pending_failed").await.unwrap();
+        db.drop().await.unwrap();
+
+        let pair = ed25519::Pair::generate().0;
+        let block = block(&pair);
+        register(&db, &block).await;
+        //utxo root of the parent can't be read, so the block fails before changing anything
+        let roots: Collection<Document> = db.collection("utxo_roots");
+        roots
+            .insert_one(doc! {"number": 1_i64, "root": "root", "state": "corrupted"})
+            .await
+            .unwrap();
+
+        let result = PendingBlock::accept(&db, &block, &mut vec![parent(&block)]).await;
+        assert!(matches!(result, Err(BlockError::Internal(_))));
+        assert_eq!(count(&db, "pending_block").await, 0);
+        assert_eq!(count(&db, "UTXOs").await, 0);
+        assert_eq!(PendingBlock::recover(&db).await, Ok(()));
+        assert_eq!(count(&db, "Blocks").await, 0);
+        db.drop().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn recovers_half_applied_block() {
+        let db = Mongodb::connect_to("Centichain_test_pending_recover").await.unwrap();
+        db.drop().await.unwrap();
+
+        let pair = ed25519::Pair::generate().0;
+        let block = block(&pair);
+        register(&db, &block).await;
+        let changes = Changes {
+            commitment: UtxoCommitment::at(2, &[]),
+            waiting: vec![(block.header.validator, 0)],
+        };
+
+        //relay stopped after generating outputs of the block and before saving its utxo root
+        PendingBlock::start(&db, &block, &changes).await.unwrap();
+        block.apply(&changes, &db).await.unwrap();
+        let roots: Collection<Document> = db.collection("utxo_roots");
+        roots.delete_many(doc! {}).await.unwrap();
+
+        assert_eq!(PendingBlock::recover(&db).await, Ok(()));
+        assert_eq!(count(&db, "pending_block").await, 0);
+        assert_eq!(count(&db, "Blocks").await, 1);
+        assert_eq!(UtxoCommitment::get(&db, 2).await, Ok(Some(changes.commitment)));
+        let utxos: Collection<Document> = db.collection("UTXOs");
+        let person = utxos.find_one(doc! {}).await.unwrap().unwrap();
+        assert_eq!(person.get_array("utxos").unwrap().len(), 1);
+        db.drop().await.unwrap();
+    }
+}
@@ -1,25 +1,18 @@
 use rust_decimal::Decimal;
-use std::str::FromStr;
+
+use crate::relay::practical::chain_params::CHAIN_PARAMS;
 
 use super::block::Block;
 
 pub struct Reward;
 
 impl Reward {
-    pub fn calculate(last_block: &mut Vec<Block>) -> Decimal {
-        let reward: Decimal;
-        if last_block.len() > 0 {
-            if last_block[0].header.number % 1500000 == 0 {
-                reward = last_block[0].body.coinbase.reward
-                    / Decimal::from_str("2.0").unwrap().round_dp(12);
-                reward
-            } else {
-                reward = last_block[0].body.coinbase.reward;
-                reward
-            }
-        } else {
-            reward = Decimal::from_str("50.0").unwrap();
-            reward
-        }
+    //calculate reward of the next block with the number of the new block
+    pub fn calculate(last_block: &[Block]) -> Decimal {
+        let number = match last_block.first() {
+            Some(last) => last.header.number + 1,
+            None => 1,
+        };
+        CHAIN_PARAMS.reward(number)
     }
 }
@@ -0,0 +1,186 @@
+use chrono::Utc;
+use libp2p::PeerId;
+use rust_decimal::Decimal;
+use sp_core::{
+    ed25519::{Pair, Public},
+    Pair as PairT,
+};
+
+use crate::relay::tools::utxo::UTXO;
+
+use super::{
+    block::header::Sign,
+    chain_params::CHAIN_PARAMS,
+    coin_selection::{Selection, Strategy},
+    transaction::{Input, Output, Script, Transaction, Unspent, MAX_DECIMALS},
+};
+
+//builder of signed transactions for wallets
+//it makes hashes and signature exactly like validation of relays does
+//then checks the transaction with the same verification before returning it
+pub struct TransactionBuilder {
+    pair: Pair,
+    utxos: Vec<UTXO>,
+    payments: Vec<(Public, Decimal)>,
+    script: Script,
+    strategy: Strategy,
+}
+
+impl TransactionBuilder {
+    pub fn new(pair: Pair) -> Self {
+        Self {
+            pair,
+            utxos: Vec::new(),
+            payments: Vec::new(),
+            script: Script::Single,
+            strategy: Strategy::default(),
+        }
+    }
+
+    //utxos of wallet that builder can spend
+    pub fn utxos(mut self, utxos: Vec<UTXO>) -> Self {
+        self.utxos = utxos;
+        self
+    }
+
+    //pay value to a wallet, value of transaction is sum of payments
+    pub fn pay(mut self, wallet: Public, value: Decimal) -> Self {
+        self.payments.push((wallet, value));
+        self
+    }
+
+    //bond value as stake of a validator, bond is the first output and goes to signer
+    pub fn stake(mut self, validator: PeerId, value: Decimal) -> Self {
+        self.payments.insert(0, (self.pair.public(), value));
+        self.script = Script::Stake(validator);
+        self
+    }
+
+    //start unbonding of stakes of signer for a validator
+    //an unbond transaction pays value to signer itself
+    pub fn unbond(mut self, validator: PeerId, value: Decimal) -> Self {
+        self.payments.push((self.pair.public(), value));
+        self.script = Script::Unbond(validator);
+        self
+    }
+
+    //coin selection strategy of inputs, default is branch and bound
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    //make, sign and verify the transaction
+    pub fn build(self) -> Result<Transaction, &'static str> {
+        if self.payments.is_empty() {
+            return Err("Transaction doesn't have any payment.");
+        }
+        if self
+            .payments
+            .iter()
+            .any(|(_, value)| *value <= Decimal::ZERO || value.scale() > MAX_DECIMALS)
+        {
+            return Err("Payment values are incorrect.");
+        }
+
+        let value: Decimal = self.payments.iter().map(|(_, value)| *value).sum();
+        let Selection { utxos, fee, change } = self.strategy.select(&self.utxos, value)?;
+        if change.scale() > MAX_DECIMALS {
+            return Err("Change of transaction has too many decimals.");
+        }
+
+        //change returns to signer with a new salt
+        let mut unspents: Vec<Unspent> = self
+            .payments
+            .iter()
+            .map(|(wallet, value)| Unspent::new(*wallet, *value))
+            .collect();
+        if change > Decimal::ZERO {
+            unspents.push(Unspent::new(self.pair.public(), change));
+        }
+
+        let input = Input::new(utxos);
+        let output = Output::new(unspents);
+        let hash = Transaction::make_hash(&input, &output, &self.script);
+        let sign = Sign {
+            signatgure: self.pair.sign(CHAIN_PARAMS.signing_payload(&hash).as_bytes()),
+            key: self.pair.public(),
+        };
+
+        let trx = Transaction {
+            hash,
+            input,
+            output,
+            value,
+            fee,
+            script: self.script,
+            signature: vec![sign],
+            date: Utc::now().to_string(),
+        };
+        trx.verify()?;
+        Ok(trx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn utxos(pair: &Pair, values: &[&str]) -> Vec<UTXO> {
+        values
+            .iter()
+            .map(|value| {
+                let unspent = Unspent::new(pair.public(), Decimal::from_str(value).unwrap());
+                UTXO::new(1, "trx", "output", &unspent)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn built_transaction_passes_verification() {
+        let pair = Pair::from_seed(&[3; 32]);
+        let receiver = Pair::from_seed(&[4; 32]).public();
+        let trx = TransactionBuilder::new(pair.clone())
+            .utxos(utxos(&pair, &["5", "20", "100"]))
+            .pay(receiver, Decimal::from(12))
+            .build()
+            .unwrap();
+
+        assert_eq!(trx.verify(), Ok(()));
+        assert_eq!(trx.signer(), &pair.public());
+        assert_eq!(trx.value, Decimal::from(12));
+        assert_eq!(trx.fee, CHAIN_PARAMS.fee(Decimal::from(12)));
+    }
+
+    #[test]
+    fn changed_built_transaction_fails_verification() {
+        let pair = Pair::from_seed(&[3; 32]);
+        let receiver = Pair::from_seed(&[4; 32]).public();
+        let trx = TransactionBuilder::new(pair.clone())
+            .utxos(utxos(&pair, &["5", "20", "100"]))
+            .pay(receiver, Decimal::from(12))
+            .build()
+            .unwrap();
+
+        let mut changed = trx.clone();
+        changed.output.unspents[0].data.value = Decimal::from(13);
+        assert!(changed.verify().is_err());
+
+        let mut changed = trx;
+        changed.signature[0].key = receiver;
+        assert!(changed.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_payment_without_enough_utxos() {
+        let pair = Pair::from_seed(&[3; 32]);
+        let receiver = Pair::from_seed(&[4; 32]).public();
+        let built = TransactionBuilder::new(pair.clone())
+            .utxos(utxos(&pair, &["5"]))
+            .pay(receiver, Decimal::from(12))
+            .build();
+        assert!(built.is_err());
+    }
+}
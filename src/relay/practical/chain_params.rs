@@ -0,0 +1,171 @@
+use std::{env, str::FromStr};
+
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+
+use super::genesis::GENESIS;
+
+//parameters of the network that this relay runs on
+//network is selected by CENTICHAIN_NETWORK environment variable (mainnet, testnet or devnet)
+//an unknown network is reported at startup by Network::from_env before anything uses it
+pub static CHAIN_PARAMS: Lazy<ChainParams> =
+    Lazy::new(|| ChainParams::new(Network::from_env().expect("CENTICHAIN_NETWORK is unknown")));
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl Network {
+    //read network from environment and use mainnet if it was not set
+    //a misspelled network must not run the relay on mainnet
+    pub fn from_env() -> Result<Self, String> {
+        match env::var("CENTICHAIN_NETWORK") {
+            Ok(network) => match network.to_lowercase().as_str() {
+                "mainnet" => Ok(Self::Mainnet),
+                "testnet" => Ok(Self::Testnet),
+                "devnet" | "local" => Ok(Self::Devnet),
+                _ => Err(format!(
+                    "Network {} is unknown, CENTICHAIN_NETWORK should be mainnet, testnet or devnet",
+                    network
+                )),
+            },
+            Err(_) => Ok(Self::Mainnet),
+        }
+    }
+
+    //upgrade height of network when there is no genesis spec that defines it
+    fn upgrade_height(&self) -> u64 {
+        match self {
+            Self::Mainnet => 1500000,
+            Self::Testnet => 150000,
+            Self::Devnet => 1,
+        }
+    }
+
+    //chain id of network when there is no genesis spec that defines it
+    fn default_chain_id(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "centichain",
+            Self::Testnet => "centichain-testnet",
+            Self::Devnet => "centichain-devnet",
+        }
+    }
+}
+
+//consensus parameters of a network
+#[derive(Debug, Clone)]
+pub struct ChainParams {
+    pub network: Network,
+    pub chain_id: String,
+    pub genesis_reward: Decimal,
+    pub halving_interval: u64,
+    pub relay_fee_share: Decimal,
+    pub fee_rate: Decimal,
+    pub min_fee: Decimal,
+    pub max_block_size: usize,
+    pub block_time: i64,
+    pub confirmations: u64,
+    pub min_stake: Decimal,
+    pub unbonding_delay: u64,
+    pub epoch_length: u64,
+    //first block that is signed over chain id and its place in chain and has domain-separated merkel root
+    pub upgrade_height: u64,
+}
+
+impl ChainParams {
+    pub fn new(network: Network) -> Self {
+        //chain id of genesis spec has priority over default chain id of network
+        let chain_id = match GENESIS.as_ref() {
+            Some(spec) => spec.chain_id.clone(),
+            None => network.default_chain_id().to_string(),
+        };
+        //chains of a genesis spec are upgraded from their first block unless spec says another height
+        let upgrade_height = GENESIS
+            .as_ref()
+            .map(|spec| spec.upgrade_height.unwrap_or(1))
+            .unwrap_or(network.upgrade_height());
+        match network {
+            Network::Mainnet => Self {
+                network,
+                chain_id,
+                genesis_reward: Decimal::from_str("50.0").unwrap(),
+                halving_interval: 1500000,
+                relay_fee_share: Decimal::from_str("0.10").unwrap(),
+                fee_rate: Decimal::from_str("0.01").unwrap(),
+                min_fee: Decimal::ZERO,
+                max_block_size: 255,
+                block_time: 59,
+                confirmations: 6,
+                min_stake: Decimal::from(1000),
+                unbonding_delay: 10000,
+                epoch_length: 10000,
+                upgrade_height,
+            },
+            Network::Testnet => Self {
+                network,
+                chain_id,
+                genesis_reward: Decimal::from_str("50.0").unwrap(),
+                halving_interval: 150000,
+                relay_fee_share: Decimal::from_str("0.10").unwrap(),
+                fee_rate: Decimal::from_str("0.01").unwrap(),
+                min_fee: Decimal::ZERO,
+                max_block_size: 255,
+                block_time: 59,
+                confirmations: 6,
+                min_stake: Decimal::from(1000),
+                unbonding_delay: 1000,
+                epoch_length: 10000,
+                upgrade_height,
+            },
+            Network::Devnet => Self {
+                network,
+                chain_id,
+                genesis_reward: Decimal::from_str("50.0").unwrap(),
+                halving_interval: 1000,
+                relay_fee_share: Decimal::from_str("0.10").unwrap(),
+                fee_rate: Decimal::from_str("0.01").unwrap(),
+                min_fee: Decimal::ZERO,
+                max_block_size: 255,
+                block_time: 10,
+                confirmations: 2,
+                min_stake: Decimal::from(100),
+                unbonding_delay: 10,
+                epoch_length: 100,
+                upgrade_height,
+            },
+        }
+    }
+
+    //payload that is signed for a hash (transactions, blocks and attestations)
+    //chain id is a part of it for preventing replay of signatures in other networks
+    pub fn signing_payload(&self, hash: &str) -> String {
+        format!("{}:{}", self.chain_id, hash)
+    }
+
+    //blocks from upgrade height follow the new signing payloads and merkel tree
+    pub fn upgraded(&self, number: u64) -> bool {
+        number >= self.upgrade_height
+    }
+
+    //fee of a transaction is fee rate of its value but not less than minimum fee
+    pub fn fee(&self, value: Decimal) -> Decimal {
+        (value * self.fee_rate).max(self.min_fee)
+    }
+
+    //reward of a block with its number, genesis block number is 1
+    //reward halves at every multiple of halving interval
+    pub fn reward(&self, number: u64) -> Decimal {
+        let halvings = number / self.halving_interval;
+        let mut reward = self.genesis_reward;
+        for _ in 0..halvings {
+            if reward.is_zero() {
+                break;
+            }
+            reward = (reward / Decimal::from(2)).round_dp(12);
+        }
+        reward
+    }
+}
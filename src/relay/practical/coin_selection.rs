@@ -0,0 +1,171 @@
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Collection, Database,
+};
+use rand::seq::SliceRandom;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::relay::tools::utxo::UTXO;
+
+use super::chain_params::CHAIN_PARAMS;
+
+//maximum number of inputs that a transaction can have
+const MAX_INPUTS: usize = u8::MAX as usize;
+//maximum number of tries of branch and bound before it falls back to largest first
+const BNB_TRIES: usize = 100_000;
+
+//strategies of selecting utxos for a payment
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    //search a set that covers value and fee exactly without change, else largest first
+    #[default]
+    BranchAndBound,
+    //fewest inputs
+    LargestFirst,
+    //consolidates dust utxos of wallet
+    SmallestFirst,
+    //random order that doesn't leak amounts of wallet's utxos
+    Random,
+}
+
+//selected utxos for a payment with its fee and change that returns to wallet
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Selection {
+    pub utxos: Vec<UTXO>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub fee: Decimal,
+    #[serde_as(as = "DisplayFromStr")]
+    pub change: Decimal,
+}
+
+impl Strategy {
+    //select utxos that cover value and its fee
+    pub fn select<'a>(&self, utxos: &[UTXO], value: Decimal) -> Result<Selection, &'a str> {
+        if value <= Decimal::ZERO {
+            return Err("Value should be positive.");
+        }
+        let fee = CHAIN_PARAMS.fee(value);
+        let target = value + fee;
+        let mut utxos = utxos.to_vec();
+
+        let selected = match self {
+            Self::BranchAndBound => match Self::branch_and_bound(&utxos, target) {
+                Some(selected) => Some(selected),
+                None => Self::LargestFirst.select(&utxos, value).ok().map(|s| s.utxos),
+            },
+            Self::LargestFirst => {
+                utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.unspent));
+                Self::accumulate(utxos, target)
+            }
+            Self::SmallestFirst => {
+                utxos.sort_by_key(|utxo| utxo.unspent);
+                Self::accumulate(utxos, target)
+            }
+            Self::Random => {
+                utxos.shuffle(&mut rand::thread_rng());
+                Self::accumulate(utxos, target)
+            }
+        };
+
+        match selected {
+            Some(utxos) => {
+                let sum: Decimal = utxos.iter().map(|utxo| utxo.unspent).sum();
+                Ok(Selection {
+                    utxos,
+                    fee,
+                    change: sum - target,
+                })
+            }
+            None => Err("You don't have enough CENTI"),
+        }
+    }
+
+    //take utxos in their order until they cover target
+    fn accumulate(utxos: Vec<UTXO>, target: Decimal) -> Option<Vec<UTXO>> {
+        let mut selected = Vec::new();
+        let mut sum = Decimal::ZERO;
+        for utxo in utxos {
+            if sum >= target || selected.len() == MAX_INPUTS {
+                break;
+            }
+            sum += utxo.unspent;
+            selected.push(utxo);
+        }
+        if sum >= target {
+            Some(selected)
+        } else {
+            None
+        }
+    }
+
+    //depth first search for a set of utxos that its sum is exactly target
+    //utxos are sorted from largest and branches that can't reach target are cut
+    fn branch_and_bound(utxos: &[UTXO], target: Decimal) -> Option<Vec<UTXO>> {
+        let mut sorted = utxos.to_vec();
+        sorted.sort_by_key(|utxo| std::cmp::Reverse(utxo.unspent));
+        //remaining[i] is sum of utxos from i to the end
+        let mut remaining = vec![Decimal::ZERO; sorted.len() + 1];
+        for i in (0..sorted.len()).rev() {
+            remaining[i] = remaining[i + 1] + sorted[i].unspent;
+        }
+        if remaining[0] < target {
+            return None;
+        }
+
+        let mut selected: Vec<usize> = Vec::new();
+        let mut sum = Decimal::ZERO;
+        let mut index = 0;
+        let mut tries = 0;
+        loop {
+            tries += 1;
+            if tries > BNB_TRIES {
+                return None;
+            }
+
+            let backtrack = if sum == target {
+                return Some(selected.iter().map(|i| sorted[*i].clone()).collect());
+            } else {
+                sum > target
+                    || index == sorted.len()
+                    || sum + remaining[index] < target
+                    || selected.len() == MAX_INPUTS
+            };
+
+            if backtrack {
+                //remove last included utxo and try the branch without it
+                match selected.pop() {
+                    Some(last) => {
+                        sum -= sorted[last].unspent;
+                        index = last + 1;
+                    }
+                    None => return None,
+                }
+            } else {
+                selected.push(index);
+                sum += sorted[index].unspent;
+                index += 1;
+            }
+        }
+    }
+}
+
+//utxos that are spent by transactions of mempool and can't be selected again
+pub async fn reserved<'a>(db: &'a Database, utxos: &[UTXO]) -> Result<Vec<String>, &'a str> {
+    let collection: Collection<Document> = db.collection("transactions");
+    let hashes: Vec<&String> = utxos.iter().map(|utxo| &utxo.unspent_hash).collect();
+    let filter = doc! {"input.utxos.unspent_hash": {"$in": hashes}};
+    match collection.distinct("input.utxos.unspent_hash", filter).await {
+        Ok(values) => Ok(values
+            .into_iter()
+            .filter_map(|value| match value {
+                Bson::String(hash) => Some(hash),
+                _ => None,
+            })
+            .collect()),
+        Err(_) => Err("Querying mempool transactions problem-(relay/practical/coin_selection 169)"),
+    }
+}
@@ -0,0 +1,176 @@
+use libp2p::futures::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    options::FindOneOptions,
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use num_bigint::BigUint;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::relay::tools::utxo::{Person, UTXO};
+
+use super::{
+    block::block::Block,
+    stake::Stake,
+    transaction::{Script, Transaction},
+};
+
+//commitment of utxo set after a block is a MuHash, product of hashes of its utxos modulo a 3072 bit prime
+//so it can be updated by multiplying outputs and dividing inputs of each block
+//state is the product that next block continues from and root is its sha256
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UtxoCommitment {
+    pub number: u64,
+    pub root: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+//2^3072 - 1103717, the largest 3072 bit safe prime
+static PRIME: Lazy<BigUint> =
+    Lazy::new(|| (BigUint::from(1u32) << 3072usize) - BigUint::from(1103717u32));
+
+//hash of a utxo in the group, it is made from unspent hash and value that identify a utxo
+//sha512 is repeated with a counter to fill 3072 bits
+fn element(utxo: &UTXO) -> BigUint {
+    let data = format!("{}:{}", utxo.unspent_hash, utxo.unspent.normalize());
+    let mut bytes = Vec::with_capacity(384);
+    for i in 0..6u8 {
+        bytes.extend(Sha512::new().chain_update([i]).chain_update(&data).finalize());
+    }
+    BigUint::from_bytes_be(&bytes) % &*PRIME
+}
+
+//product of hashes of utxos
+fn product<'a>(utxos: impl Iterator<Item = &'a UTXO>) -> BigUint {
+    utxos.fold(BigUint::from(1u32), |acc, utxo| acc * element(utxo) % &*PRIME)
+}
+
+fn root(state: &BigUint) -> String {
+    let bytes = state.to_bytes_be();
+    let mut padded = vec![0u8; 384 - bytes.len()];
+    padded.extend(bytes);
+    Sha256::digest(padded)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl UtxoCommitment {
+    fn new(number: u64, state: BigUint) -> Self {
+        Self {
+            number,
+            root: root(&state),
+            state: state.to_str_radix(16),
+        }
+    }
+
+    //commitment of a set of utxos from scratch at a block
+    pub fn at(number: u64, utxos: &[UTXO]) -> Self {
+        Self::new(number, product(utxos.iter()))
+    }
+
+    //root of a set of utxos from scratch
+    pub fn of(utxos: &[UTXO]) -> String {
+        root(&product(utxos.iter()))
+    }
+
+    //last saved commitment
+    pub async fn last(db: &Database) -> Result<Option<Self>, &str> {
+        let collection: Collection<Document> = db.collection("utxo_roots");
+        let options = FindOneOptions::builder().sort(doc! {"number": -1}).build();
+        match collection.find_one(doc! {}).with_options(options).await {
+            Ok(opt) => Ok(opt.map(|doc| from_document(doc).unwrap())),
+            Err(_) => Err("Querying utxo roots problem-(relay/practical/commitment 86)"),
+        }
+    }
+
+    //commitment after a block
+    pub async fn get(db: &Database, number: u64) -> Result<Option<Self>, &str> {
+        let collection: Collection<Document> = db.collection("utxo_roots");
+        match collection.find_one(doc! {"number": number as i64}).await {
+            Ok(opt) => Ok(opt.map(|doc| from_document(doc).unwrap())),
+            Err(_) => Err("Querying utxo root problem-(relay/practical/commitment 95)"),
+        }
+    }
+
+    pub async fn save<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("utxo_roots");
+        let filter = doc! {"number": self.number as i64};
+        let update = doc! {"$set": to_document(self).unwrap()};
+        match collection.update_one(filter, update).upsert(true).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Saving utxo root problem-(relay/practical/commitment 105)"),
+        }
+    }
+
+    //utxo set of chain from database, inputs of mempool transactions are removed from UTXOs
+    //at their admission, so they are still a part of chain state
+    pub async fn chain_utxos(db: &Database) -> Result<Vec<UTXO>, &str> {
+        let mut utxos = Vec::new();
+        let utxos_collection: Collection<Document> = db.collection("UTXOs");
+        match utxos_collection.find(doc! {}).await {
+            Ok(mut cursor) => {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    utxos.extend(from_document::<Person>(doc).unwrap().utxos);
+                }
+            }
+            Err(_) => return Err("Querying utxos problem-(relay/practical/commitment 120)"),
+        }
+
+        let trx_collection: Collection<Document> = db.collection("transactions");
+        match trx_collection.find(doc! {}).await {
+            Ok(mut cursor) => {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    let trx: Transaction = from_document(doc).unwrap();
+                    utxos.extend(trx.inputs().iter().cloned());
+                }
+            }
+            Err(_) => return Err("Querying transactions problem-(relay/practical/commitment 131)"),
+        }
+        Ok(utxos)
+    }
+
+    //commitment of utxo set after applying a block to the set of its parent
+    //it should be made before applying the block, so its result can reject the block
+    pub async fn next<'a>(db: &'a Database, block: &Block) -> Result<Self, &'a str> {
+        let number = block.header.number;
+        let previous = match Self::get(db, number.saturating_sub(1)).await? {
+            Some(previous) => match BigUint::parse_bytes(previous.state.as_bytes(), 16) {
+                Some(state) => state,
+                None => return Err("Utxo root is corrupted-(relay/practical/commitment 143)"),
+            },
+            //relays that synced from a dump don't have roots of old blocks
+            None => product(Self::chain_utxos(db).await?.iter()),
+        };
+
+        let mut added = Vec::new();
+        let mut spent = Vec::new();
+        for unspent in &block.body.coinbase.output.unspents {
+            added.push(UTXO::new(
+                number,
+                &block.body.coinbase.hash,
+                &block.body.coinbase.output.hash,
+                unspent,
+            ));
+        }
+        for trx in &block.body.transactions {
+            spent.extend(trx.inputs().iter().cloned());
+            //bond of a stake transaction is locked and it isn't a utxo
+            let locked = matches!(trx.script, Script::Stake(_)) as usize;
+            for unspent in trx.output.unspents.iter().skip(locked) {
+                added.push(UTXO::new(number, &trx.hash, &trx.output.hash, unspent));
+            }
+        }
+        for stake in Stake::releasable(number, db).await? {
+            added.push(stake.utxo);
+        }
+
+        //dividing by spent utxos is multiplying by their inverse, that is their product ^ (prime - 2)
+        let inverse = product(spent.iter()).modpow(&(&*PRIME - 2u32), &PRIME);
+        let state = previous * product(added.iter()) % &*PRIME * inverse % &*PRIME;
+        Ok(Self::new(number, state))
+    }
+}
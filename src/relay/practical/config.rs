@@ -0,0 +1,133 @@
+use std::{env, fs};
+
+use once_cell::sync::Lazy;
+use sp_core::ed25519::Public;
+
+use super::keystore::Keystore;
+
+//configuration of this node that is read from environment variables
+//defaults are same as a mainnet relay
+pub static CONFIG: Lazy<NodeConfig> = Lazy::new(NodeConfig::from_env);
+
+#[derive(Debug, Clone)]
+pub struct NodeConfig {
+    pub wallet_path: String,
+    pub keystore_path: Option<String>,
+    pub mongo_uri: String,
+    pub listen: String,
+    pub rpc_addr: String,
+    //admin api is served only when its token is configured
+    pub admin_addr: String,
+    pub admin_token: Option<String>,
+    pub keypair_path: Option<String>,
+    pub bootstrap: Vec<String>,
+    pub data_dir: Option<String>,
+    pub genesis_path: String,
+    pub log: LogConfig,
+}
+
+//where and how relay writes its logs
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    //filter of levels like `info` or `info,relay_node::relay::events=debug`
+    pub filter: String,
+    pub json: bool,
+    //file of logs or `stdout`
+    pub path: String,
+    //size of log file in megabytes that it is rotated after it
+    pub max_size: u64,
+    //`daily`, `hourly` or `never`
+    pub rotation: String,
+    //rotated files that are kept
+    pub max_files: usize,
+}
+
+impl NodeConfig {
+    fn from_env() -> Self {
+        let var = |key: &str, default: &str| env::var(key).unwrap_or(default.to_string());
+        Self {
+            wallet_path: var("CENTICHAIN_WALLET", "/etc/wallet.dat"),
+            keystore_path: env::var("CENTICHAIN_KEYSTORE").ok(),
+            mongo_uri: var("CENTICHAIN_MONGO", "mongodb://localhost:27017"),
+            listen: var("CENTICHAIN_LISTEN", "/ip4/0.0.0.0/tcp/0"),
+            rpc_addr: var("CENTICHAIN_RPC", "0.0.0.0:33369"),
+            admin_addr: var("CENTICHAIN_ADMIN", "127.0.0.1:33370"),
+            admin_token: env::var("CENTICHAIN_ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty()),
+            keypair_path: env::var("CENTICHAIN_KEYPAIR").ok(),
+            bootstrap: env::var("CENTICHAIN_BOOTSTRAP")
+                .map(|addrs| {
+                    addrs
+                        .split(',')
+                        .map(|addr| addr.trim().to_string())
+                        .filter(|addr| !addr.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            data_dir: env::var("CENTICHAIN_DATA_DIR").ok(),
+            genesis_path: var("CENTICHAIN_GENESIS", "/etc/genesis.json"),
+            log: LogConfig {
+                filter: var("CENTICHAIN_LOG", "info"),
+                json: var("CENTICHAIN_LOG_FORMAT", "text") == "json",
+                path: var(
+                    "CENTICHAIN_LOG_PATH",
+                    if cfg!(windows) {
+                        "relaylog.dat"
+                    } else {
+                        "/etc/relaylog.dat"
+                    },
+                ),
+                max_size: var("CENTICHAIN_LOG_MAX_SIZE", "10").parse().unwrap_or(10),
+                rotation: var("CENTICHAIN_LOG_ROTATION", "daily"),
+                max_files: var("CENTICHAIN_LOG_MAX_FILES", "5").parse().unwrap_or(5),
+            },
+        }
+    }
+
+    //read wallet address of relay from keystore if it was configured, else from wallet file
+    pub fn wallet(&self) -> Result<Public, String> {
+        if let Some(path) = &self.keystore_path {
+            return Keystore::load(path).map(|keystore| keystore.public);
+        }
+        match fs::read_to_string(&self.wallet_path) {
+            Ok(content) => {
+                let wallet_addr: String = content.lines().map(|line| line.trim()).collect();
+                match wallet_addr.parse() {
+                    Ok(wallet) => Ok(wallet),
+                    Err(_) => Err(format!("Wallet address is incorrect: {}", wallet_addr)),
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    //directory that zip file of blockchain is made in and served from
+    pub fn serve_dir(&self) -> String {
+        match &self.data_dir {
+            Some(dir) => dir.clone(),
+            None => "/home".to_string(),
+        }
+    }
+
+    //zip file of blockchain for syncing new relays
+    pub fn zip_path(&self) -> String {
+        format!("{}/Centichain.zip", self.serve_dir())
+    }
+
+    //directory that mongodump writes database into it
+    pub fn dump_dir(&self) -> String {
+        match &self.data_dir {
+            Some(dir) => format!("{}/dump", dir),
+            None => "/etc/dump".to_string(),
+        }
+    }
+
+    //directory that downloaded blockchain is extracted into it
+    pub fn sync_dir(&self) -> String {
+        match &self.data_dir {
+            Some(dir) => format!("{}/sync", dir),
+            None => "./etc/dump/Centichain".to_string(),
+        }
+    }
+}
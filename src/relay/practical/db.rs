@@ -1,18 +1,24 @@
 use mongodb::{Client, Database};
 
+use super::config::CONFIG;
+
 pub struct Mongodb;
 
 impl Mongodb {
     pub async fn connect<'a>() -> Result<Database, &'a str> {
-        let uri = "mongodb://localhost:27017";
-        let connection = Client::with_uri_str(uri).await;
+        Self::connect_to("Centichain").await
+    }
+
+    //connect to another database of mongodb, like the database that reindex rebuilds chain in it
+    pub async fn connect_to<'a>(name: &str) -> Result<Database, &'a str> {
+        let connection = Client::with_uri_str(&CONFIG.mongo_uri).await;
 
         match connection {
             Ok(client) => {
-                let db = client.database("Centichain");
+                let db = client.database(name);
                 Ok(db)
             }
-            Err(_) => Err("Database Connection Problem-(db-12)"),
+            Err(_) => Err("Database Connection Problem-(db-19)"),
         }
     }
 }
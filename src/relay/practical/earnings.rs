@@ -0,0 +1,254 @@
+use libp2p::futures::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    options::FindOptions,
+    Collection, Database,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use sp_core::ed25519::Public;
+
+use super::{block::block::Block, chain_params::CHAIN_PARAMS};
+
+//an output of a coinbase that pays wallet of this relay
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Earning {
+    pub wallet: Public,
+    pub block: u64,
+    pub epoch: u64,
+    pub coinbase: String,
+    pub unspent_hash: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub value: Decimal,
+    pub date: String,
+}
+
+//earnings of relay in an epoch of blocks
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EpochEarnings {
+    pub epoch: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+    pub blocks: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub total: Decimal,
+}
+
+//earnings report of a wallet
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EarningsReport {
+    pub wallet: Public,
+    #[serde_as(as = "DisplayFromStr")]
+    pub total: Decimal,
+    pub epochs: Vec<EpochEarnings>,
+}
+
+impl Earning {
+    //index coinbase outputs of blocks that are not indexed yet for a wallet
+    pub async fn index<'a>(db: &'a Database, wallet: &Public) -> Result<(), &'a str> {
+        let state_collection: Collection<Document> = db.collection("earnings_state");
+        let earnings_collection: Collection<Document> = db.collection("earnings");
+        let blocks_collection: Collection<Document> = db.collection("Blocks");
+
+        //last block that is indexed for wallet
+        let state_filter = doc! {"wallet": wallet.to_string()};
+        let last = match state_collection.find_one(state_filter.clone()).await {
+            Ok(Some(doc)) => doc.get_i64("last").unwrap_or(0),
+            Ok(None) => 0,
+            Err(_) => return Err("Querying earnings state problem-(relay/practical/earnings 62)"),
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! {"header.number": 1})
+            .build();
+        let mut cursor = match blocks_collection
+            .find(doc! {"header.number": {"$gt": last}})
+            .with_options(options)
+            .await
+        {
+            Ok(cursor) => cursor,
+            Err(_) => return Err("Querying blocks problem-(relay/practical/earnings 74)"),
+        };
+
+        let mut indexed = last;
+        while let Some(Ok(doc)) = cursor.next().await {
+            let block: Block = from_document(doc).unwrap();
+            for unspent in &block.body.coinbase.output.unspents {
+                if unspent.data.wallet == *wallet {
+                    let earning = Self {
+                        wallet: *wallet,
+                        block: block.header.number,
+                        epoch: block.header.number / CHAIN_PARAMS.epoch_length,
+                        coinbase: block.body.coinbase.hash.clone(),
+                        unspent_hash: unspent.hash.clone(),
+                        value: unspent.data.value,
+                        date: block.header.date().to_string(),
+                    };
+                    //indexing again after a stop before saving state must not duplicate earnings
+                    let filter = doc! {
+                        "block": earning.block as i64,
+                        "wallet": wallet.to_string(),
+                        "unspent_hash": &earning.unspent_hash,
+                    };
+                    if earnings_collection
+                        .replace_one(filter, to_document(&earning).unwrap())
+                        .upsert(true)
+                        .await
+                        .is_err()
+                    {
+                        return Err("Saving earning problem-(relay/practical/earnings 103)");
+                    }
+                }
+            }
+            indexed = block.header.number as i64;
+        }
+
+        let update = doc! {"$set": {"last": indexed}};
+        match state_collection
+            .update_one(state_filter, update)
+            .upsert(true)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Updating earnings state problem-(relay/practical/earnings 117)"),
+        }
+    }
+
+    //all of indexed earnings of a wallet ordered by block
+    pub async fn all<'a>(db: &'a Database, wallet: &Public) -> Result<Vec<Self>, &'a str> {
+        let collection: Collection<Document> = db.collection("earnings");
+        let options = FindOptions::builder().sort(doc! {"block": 1}).build();
+        match collection
+            .find(doc! {"wallet": wallet.to_string()})
+            .with_options(options)
+            .await
+        {
+            Ok(mut cursor) => {
+                let mut earnings = Vec::new();
+                while let Some(Ok(doc)) = cursor.next().await {
+                    earnings.push(from_document(doc).unwrap());
+                }
+                Ok(earnings)
+            }
+            Err(_) => Err("Querying earnings problem-(relay/practical/earnings 137)"),
+        }
+    }
+
+    //csv of earnings for accounting
+    pub fn csv(earnings: &[Self]) -> String {
+        let mut csv = String::from("block,epoch,date,coinbase,unspent_hash,value\n");
+        for earning in earnings {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                earning.block,
+                earning.epoch,
+                earning.date,
+                earning.coinbase,
+                earning.unspent_hash,
+                earning.value
+            ));
+        }
+        csv
+    }
+}
+
+impl EarningsReport {
+    //make report of indexed earnings of wallet per epoch
+    pub async fn make<'a>(db: &'a Database, wallet: &Public) -> Result<Self, &'a str> {
+        let earnings = Earning::all(db, wallet).await?;
+
+        let mut epochs: Vec<EpochEarnings> = Vec::new();
+        for earning in &earnings {
+            match epochs.last_mut() {
+                Some(epoch) if epoch.epoch == earning.epoch => {
+                    if epoch.last_block != earning.block {
+                        epoch.blocks += 1;
+                    }
+                    epoch.last_block = earning.block;
+                    epoch.total += earning.value;
+                }
+                _ => epochs.push(EpochEarnings {
+                    epoch: earning.epoch,
+                    first_block: earning.block,
+                    last_block: earning.block,
+                    blocks: 1,
+                    total: earning.value,
+                }),
+            }
+        }
+
+        Ok(Self {
+            wallet: *wallet,
+            total: earnings.iter().map(|earning| earning.value).sum(),
+            epochs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+    use serde_json::json;
+    use sp_core::{ed25519, Pair};
+
+    use crate::relay::practical::db::Mongodb;
+
+    use super::*;
+
+    //block at number 1 that pays 50 to wallet in its coinbase
+    fn block(pair: &ed25519::Pair) -> Block {
+        serde_json::from_value(json!({
+            "header": {
+                "number": 1,
+                "hash": "hash-1",
+                "previous": "hash-0",
+                "validator": PeerId::random(),
+                "relay": PeerId::random(),
+                "merkel": "First",
+                "signature": {"signatgure": pair.sign(b""), "key": pair.public()},
+                "date": "2024-01-01 00:00:00 UTC",
+            },
+            "body": {
+                "coinbase": {
+                    "hash": "coinbase",
+                    "size": 0,
+                    "merkel": "First",
+                    "reward": "50",
+                    "output": {"hash": "output", "number": 1, "unspents": [{
+                        "hash": "unspent",
+                        "data": {"wallet": pair.public(), "salt": 0, "value": "50"},
+                    }]},
+                    "fees": "0",
+                    "relay_fee": "0",
+                    "validator_fee": "0",
+                },
+                "transactions": [],
+            },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn report_reads_only_indexed_earnings() {
+        let db = Mongodb::connect_to("Centichain_test_earnings").await.unwrap();
+        db.drop().await.unwrap();
+
+        let pair = ed25519::Pair::generate().0;
+        let blocks: Collection<Document> = db.collection("Blocks");
+        blocks.insert_one(to_document(&block(&pair)).unwrap()).await.unwrap();
+
+        //report doesn't index the block by itself
+        let report = EarningsReport::make(&db, &pair.public()).await.unwrap();
+        assert_eq!(report.total, Decimal::ZERO);
+
+        Earning::index(&db, &pair.public()).await.unwrap();
+        let report = EarningsReport::make(&db, &pair.public()).await.unwrap();
+        assert_eq!(report.total, Decimal::from(50));
+        assert_eq!(report.epochs.len(), 1);
+    }
+}
@@ -0,0 +1,166 @@
+use libp2p::{PeerId, Swarm};
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::ed25519::Public;
+use tracing::{error, warn};
+
+use crate::relay::{
+    events::{connections::ConnectionsHandler, gossip_messages::GossipMessages},
+    tools::{metrics::METRICS, wrongdoer::WrongDoer, HashMaker},
+};
+
+use super::{
+    block::header::Header, leader::Leader, swarm::CentichainBehaviour, validator::Validator,
+};
+
+//number of heights that signed headers are kept for detecting equivocation
+const KEPT_HEIGHTS: u64 = 1000;
+
+//evidence of equivocation is two conflicting headers that a validator signed for one height
+//it is portable and every relay can verify it with the registered wallet of the validator
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Evidence {
+    pub id: String,
+    pub validator: PeerId,
+    pub number: u64,
+    pub first: Header,
+    pub second: Header,
+}
+
+impl Evidence {
+    //make evidence from two headers, id doesn't depend on order of headers
+    fn new(first: Header, second: Header) -> Self {
+        let mut hashes = [first.hash.clone(), second.hash.clone()];
+        hashes.sort();
+        Self {
+            id: HashMaker::generate(&hashes.concat()),
+            validator: first.validator,
+            number: first.number,
+            first,
+            second,
+        }
+    }
+
+    //headers should be signed by one validator with its registered wallet for one height and have different hashes
+    pub fn verify(&self, wallet: &Public) -> bool {
+        let mut hashes = [self.first.hash.clone(), self.second.hash.clone()];
+        hashes.sort();
+        self.first.number == self.number
+            && self.second.number == self.number
+            && self.first.validator == self.validator
+            && self.second.validator == self.validator
+            && self.first.signature.key == *wallet
+            && self.second.signature.key == *wallet
+            && self.first.hash != self.second.hash
+            && self.id == HashMaker::generate(&hashes.concat())
+            && self.first.verify_sign()
+            && self.second.verify_sign()
+    }
+
+    //keep signed header of a received block and return evidence if its validator signed another one at its height
+    //headers that are not signed by registered wallet of their validator are not kept
+    pub async fn observe<'a>(db: &'a Database, header: &Header) -> Result<Option<Self>, &'a str> {
+        match Validator::get(db, &header.validator).await? {
+            Some(validator) if validator.wallet == header.signature.key && header.verify_sign() => {}
+            _ => return Ok(None),
+        }
+
+        let collection: Collection<Document> = db.collection("headers");
+        let filter = doc! {"number": header.number as i64, "validator": header.validator.to_string()};
+        match collection.find_one(filter).await {
+            Ok(Some(doc)) => {
+                let stored: Header = from_document(doc).unwrap();
+                if stored.hash != header.hash {
+                    Ok(Some(Self::new(stored, header.clone())))
+                } else {
+                    Ok(None)
+                }
+            }
+            Ok(None) => {
+                if collection
+                    .insert_one(to_document(header).unwrap())
+                    .await
+                    .is_err()
+                {
+                    return Err("Inserting signed header problem-(relay/practical/evidence 88)");
+                }
+
+                //remove old headers
+                let old = header.number.saturating_sub(KEPT_HEIGHTS) as i64;
+                match collection.delete_many(doc! {"number": {"$lt": old}}).await {
+                    Ok(_) => Ok(None),
+                    Err(_) => Err("Deleting old headers problem-(relay/practical/evidence 95)"),
+                }
+            }
+            Err(_) => Err("Querying signed headers problem-(relay/practical/evidence 98)"),
+        }
+    }
+
+    //save evidence and return false if it was saved before
+    async fn insertion<'a>(&self, db: &'a Database) -> Result<bool, &'a str> {
+        let collection: Collection<Document> = db.collection("evidences");
+        match collection.find_one(doc! {"id": &self.id}).await {
+            Ok(Some(_)) => Ok(false),
+            Ok(None) => match collection.insert_one(to_document(self).unwrap()).await {
+                Ok(_) => Ok(true),
+                Err(_) => Err("Inserting evidence problem-(relay/practical/evidence 109)"),
+            },
+            Err(_) => Err("Querying evidences problem-(relay/practical/evidence 111)"),
+        }
+    }
+
+    //save a new evidence, gossip it to other relays and validators
+    //then slash and remove the validator and start voting if it was leader
+    pub async fn handle<'a>(
+        &self,
+        db: &'a Database,
+        leader: &mut Leader,
+        connections_handler: &mut ConnectionsHandler,
+        swarm: &mut Swarm<CentichainBehaviour>,
+    ) -> Result<(), &'a str> {
+        let verified = match Validator::get(db, &self.validator).await? {
+            Some(validator) => self.verify(&validator.wallet),
+            None => false,
+        };
+        if !verified {
+            warn!("Evidence is incorrect or its validator is unknown and rejected.");
+            return Ok(());
+        }
+        if !self.insertion(db).await? {
+            return Ok(());
+        }
+        warn!(
+            "Equivocation of {} at height {} detected",
+            self.validator, self.number
+        );
+
+        let str_gossip_message =
+            serde_json::to_string(&GossipMessages::Evidence(self.clone())).unwrap();
+        for topic in ["relay", "validator"] {
+            if let Err(e) = swarm
+                .behaviour_mut()
+                .gossipsub
+                .publish(CentichainBehaviour::topic(topic), str_gossip_message.clone())
+            {
+                METRICS.gossip_failed(topic);
+                error!("Gossiping evidence problem: {}", e);
+            }
+        }
+
+        WrongDoer::new(
+            self.validator,
+            &format!("Equivocation at height {}", self.number),
+        )
+        .slash(db)
+        .await?;
+
+        if leader.peerid == Some(self.validator) {
+            leader.start_voting(db, connections_handler, swarm).await
+        } else {
+            connections_handler.remove(db, self.validator, swarm).await
+        }
+    }
+}
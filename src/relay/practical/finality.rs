@@ -0,0 +1,314 @@
+use std::collections::HashSet;
+
+use libp2p::{
+    futures::StreamExt,
+    identity::{Keypair, PublicKey},
+    PeerId, Swarm,
+};
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    options::FindOneOptions,
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::Pair;
+use tracing::{error, info, warn};
+
+use crate::relay::{
+    events::gossip_messages::GossipMessages,
+    tools::metrics::METRICS,
+};
+
+use super::{
+    block::{block::Block, header::Sign},
+    chain_params::CHAIN_PARAMS,
+    keystore::Keystore,
+    reciept::Reciept,
+    relay::RelayWallet,
+    swarm::CentichainBehaviour,
+};
+
+//relays of blocks in this many blocks up to a height are the relays that attest to it
+const RELAYS_WINDOW: u64 = 1000;
+
+//attestation is a relay's signature on a block hash at a height that has enough confirmations
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Attestation {
+    pub number: u64,
+    pub hash: String,
+    pub relay: PeerId,
+    pub signature: Vec<u8>,
+    pub wallet_sign: Option<Sign>,
+}
+
+//checkpoint is a finalized block that a quorum of relays attested to it
+//blocks below the last checkpoint can not be replaced
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub number: u64,
+    pub hash: String,
+    pub attestations: Vec<Attestation>,
+}
+
+impl Attestation {
+    //message that relays sign for a block
+    fn message(number: u64, hash: &String) -> String {
+        CHAIN_PARAMS.signing_payload(&format!("{}:{}", number, hash))
+    }
+
+    //make a new attestation and sign it by keypair of relay and its wallet if relay has a keystore
+    pub fn new<'a>(keypair: &Keypair, number: u64, hash: String) -> Result<Self, &'a str> {
+        let relay = PeerId::from_public_key(&keypair.public());
+        let message = Self::message(number, &hash);
+        match keypair.sign(message.as_bytes()) {
+            Ok(signature) => Ok(Self {
+                number,
+                hash,
+                relay,
+                signature,
+                wallet_sign: Keystore::sign(&message),
+            }),
+            Err(_) => Err("Signing attestation problem-(relay/practical/finality 71)"),
+        }
+    }
+
+    //check signature of attestation with public key of relay that is inside its peerid
+    //and wallet signature of it if there is
+    pub fn verify(&self) -> bool {
+        let message = Self::message(self.number, &self.hash);
+        if let Some(sign) = &self.wallet_sign {
+            if !sp_core::ed25519::Pair::verify(&sign.signatgure, &message, &sign.key) {
+                return false;
+            }
+        }
+        let multihash = self.relay.as_ref();
+        match PublicKey::try_decode_protobuf(multihash.digest()) {
+            Ok(public_key) => public_key.verify(message.as_bytes(), &self.signature),
+            Err(_) => false,
+        }
+    }
+
+    //after a new block added, attest to the block that has enough confirmations now
+    //then gossip the attestation to other relays and check quorum for making checkpoint
+    pub async fn attest<'a>(
+        db: &'a Database,
+        swarm: &mut Swarm<CentichainBehaviour>,
+        keypair: &Keypair,
+        last_number: u64,
+    ) -> Result<(), &'a str> {
+        //relays attest to a block after enough blocks placed on top of it
+        let confirmations = CHAIN_PARAMS.confirmations;
+        if last_number < confirmations {
+            return Ok(());
+        }
+
+        let number = last_number - confirmations;
+        let collection: Collection<Document> = db.collection("Blocks");
+        match collection.find_one(doc! {"header.number": number as i64}).await {
+            Ok(Some(doc)) => {
+                let block: Block = match from_document(doc) {
+                    Ok(block) => block,
+                    Err(_) => return Err("Reading block for attestation problem-(relay/practical/finality 111)"),
+                };
+                let attestation = Self::new(keypair, number, block.header.hash)?;
+
+                //gossip attestation to relays, it is not fatal if there is no any relays to recieve it
+                let gossip_message = GossipMessages::Attestation(attestation.clone());
+                let str_gossip_message = serde_json::to_string(&gossip_message).unwrap();
+                if let Err(e) = swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(CentichainBehaviour::topic("relay"), str_gossip_message)
+                {
+                    METRICS.gossip_failed("relay");
+                    error!("Gossiping attestation problem: {}", e);
+                }
+
+                attestation.insertion(db).await
+            }
+            Ok(None) => Ok(()),
+            Err(_) => Err("Querying block for attestation problem-(relay/practical/finality 130)"),
+        }
+    }
+
+    //handle an attestation that recieved from other relays
+    //only attestations of relays of the chain at the attested height are kept
+    pub async fn handle<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        if !self.verify() {
+            warn!("Attestation signature is wrong: {}", self.relay);
+            return Ok(());
+        }
+        if !Checkpoint::relays(db, self.number).await?.contains(&self.relay) {
+            warn!("Attestation of unknown relay rejected: {}", self.relay);
+            return Ok(());
+        }
+        //wallet signature should be made by the wallet that relay announced
+        //relay's own signature is enough if this relay doesn't know its wallet yet
+        if let Some(sign) = &self.wallet_sign {
+            match RelayWallet::get(db, &self.relay).await? {
+                Some(wallet) if wallet != sign.key => {
+                    warn!("Attestation wallet is not the wallet of relay: {}", self.relay);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        //attestation should be for a block that exists in this relay's chain
+        let collection: Collection<Document> = db.collection("Blocks");
+        let filter = doc! {"header.number": self.number as i64, "header.hash": &self.hash};
+        match collection.find_one(filter).await {
+            Ok(Some(_)) => self.insertion(db).await,
+            Ok(None) => {
+                warn!(
+                    "Attestation for unknown block {} recieved from: {}",
+                    self.number, self.relay
+                );
+                Ok(())
+            }
+            Err(_) => Err("Querying block of attestation problem-(relay/practical/finality 169)"),
+        }
+    }
+
+    //insert attestation if relay didn't attest to this height before, then check quorum
+    async fn insertion<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("attestations");
+        let filter = doc! {"number": self.number as i64, "relay": self.relay.to_string()};
+        match collection.find_one(filter).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => match collection.insert_one(to_document(self).unwrap()).await {
+                Ok(_) => Checkpoint::make(db, self.number, &self.hash).await,
+                Err(_) => Err("Inserting attestation problem-(relay/practical/finality 181)"),
+            },
+            Err(_) => Err("Querying attestations problem-(relay/practical/finality 183)"),
+        }
+    }
+}
+
+impl Checkpoint {
+    //relays that attest to a height are relays of blocks in the window up to it
+    //they are read from the chain, so every relay with the same chain has the same relays
+    pub async fn relays(db: &Database, number: u64) -> Result<HashSet<PeerId>, &str> {
+        let collection: Collection<Document> = db.collection("Blocks");
+        let from = number.saturating_sub(RELAYS_WINDOW) as i64;
+        let filter = doc! {"header.number": {"$gt": from, "$lte": number as i64}};
+        match collection.distinct("header.relay", filter).await {
+            Ok(relays) => {
+                let mut peerids = HashSet::new();
+                for relay in relays {
+                    match relay.as_str().map(str::parse::<PeerId>) {
+                        Some(Ok(peerid)) => {
+                            peerids.insert(peerid);
+                        }
+                        _ => return Err("Reading relays of blocks problem-(relay/practical/finality 203)"),
+                    }
+                }
+                Ok(peerids)
+            }
+            Err(_) => Err("Querying relays of blocks problem-(relay/practical/finality 208)"),
+        }
+    }
+
+    //quorum is more than 2/3 of relays of the chain
+    fn quorum(relays: &HashSet<PeerId>) -> usize {
+        relays.len() * 2 / 3 + 1
+    }
+
+    //get last checkpoint from database
+    pub async fn last(db: &Database) -> Result<Option<Self>, &str> {
+        let collection: Collection<Document> = db.collection("checkpoints");
+        let option = FindOneOptions::builder().sort(doc! {"number": -1}).build();
+        match collection.find_one(doc! {}).with_options(option).await {
+            Ok(Some(doc)) => match from_document(doc) {
+                Ok(checkpoint) => Ok(Some(checkpoint)),
+                Err(_) => Err("Reading last checkpoint problem-(relay/practical/finality 224)"),
+            },
+            Ok(None) => Ok(None),
+            Err(_) => Err("Problem during get last checkpoint-(relay/practical/finality 227)"),
+        }
+    }
+
+    //make a checkpoint if attestations of a block reached quorum
+    //and then set reciepts of the block as finalized
+    async fn make<'a>(db: &'a Database, number: u64, hash: &String) -> Result<(), &'a str> {
+        //checkpoints only move forward
+        match Self::last(db).await? {
+            Some(last) if last.number >= number => return Ok(()),
+            _ => {}
+        }
+
+        //only attestations of relays of the chain at this height are counted
+        let relays = Self::relays(db, number).await?;
+        let collection: Collection<Document> = db.collection("attestations");
+        let filter = doc! {"number": number as i64, "hash": hash};
+        let mut attestations = Vec::new();
+        match collection.find(filter).await {
+            Ok(mut cursor) => {
+                while let Some(doc) = cursor.next().await {
+                    let attestation = match doc.map(from_document::<Attestation>) {
+                        Ok(Ok(attestation)) => attestation,
+                        _ => return Err("Reading attestations problem-(relay/practical/finality 250)"),
+                    };
+                    if relays.contains(&attestation.relay) {
+                        attestations.push(attestation);
+                    }
+                }
+            }
+            Err(_) => {
+                return Err("Querying attestations problem-(relay/practical/finality 258)")
+            }
+        }
+
+        if attestations.len() < Self::quorum(&relays) {
+            return Ok(());
+        }
+
+        let checkpoint = Self {
+            number,
+            hash: hash.clone(),
+            attestations,
+        };
+        let c_collection: Collection<Document> = db.collection("checkpoints");
+        match c_collection.insert_one(to_document(&checkpoint).unwrap()).await {
+            Ok(_) => {
+                info!("New checkpoint: block {} finalized", number);
+                let b_collection: Collection<Document> = db.collection("Blocks");
+                match b_collection.find_one(doc! {"header.number": number as i64}).await {
+                    Ok(Some(doc)) => match from_document::<Block>(doc) {
+                        Ok(block) => Reciept::finalization(db, &block).await,
+                        Err(_) => Err("Reading finalized block problem-(relay/practical/finality 279)"),
+                    },
+                    Ok(None) => Ok(()),
+                    Err(_) => Err("Querying finalized block problem-(relay/practical/finality 282)"),
+                }
+            }
+            Err(_) => Err("Inserting checkpoint problem-(relay/practical/finality 285)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::relay::practical::db::Mongodb;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB on CENTICHAIN_MONGO"]
+    async fn relays_are_read_from_blocks_up_to_height() {
+        let db = Mongodb::connect_to("Centichain_test_finality").await.unwrap();
+        db.drop().await.unwrap();
+
+        let (first, second) = (PeerId::random(), PeerId::random());
+        let collection: Collection<Document> = db.collection("Blocks");
+        for (number, relay) in [(1_i64, first), (2, first), (1500, second)] {
+            let block = doc! {"header": {"number": number, "relay": relay.to_string()}};
+            collection.insert_one(block).await.unwrap();
+        }
+
+        assert_eq!(Checkpoint::relays(&db, 2).await, Ok(HashSet::from([first])));
+        assert_eq!(Checkpoint::relays(&db, 1500).await, Ok(HashSet::from([second])));
+        assert_eq!(Checkpoint::quorum(&HashSet::from([first, second])), 2);
+        db.drop().await.unwrap();
+    }
+}
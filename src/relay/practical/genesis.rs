@@ -0,0 +1,176 @@
+use std::fs;
+
+use libp2p::PeerId;
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    Collection, Database,
+};
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use sp_core::ed25519::Public;
+use tracing::info;
+
+use crate::relay::tools::{utxo::UTXO, HashMaker};
+
+use super::{
+    commitment::UtxoCommitment,
+    config::CONFIG,
+    transaction::{Unspent, UnspentData},
+    validator::Validator,
+};
+
+//previous hash of the first block in networks that don't have a genesis spec (mainnet)
+pub const LEGACY_GENESIS: &str = "This Is The Genesis Block";
+
+//genesis spec that is loaded from configured genesis file if it exists
+//an incorrect file is reported at startup by GenesisSpec::load before anything uses it
+pub static GENESIS: Lazy<Option<GenesisSpec>> =
+    Lazy::new(|| GenesisSpec::load(&CONFIG.genesis_path).ok().flatten());
+
+//an initial allocation of coins to a wallet
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Allocation {
+    pub wallet: Public,
+    #[serde_as(as = "DisplayFromStr")]
+    pub value: Decimal,
+}
+
+//a validator that is in the network from genesis
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GenesisValidator {
+    pub peerid: PeerId,
+    pub relay: PeerId,
+    pub wallet: Public,
+}
+
+//genesis spec defines chain id, initial allocations and initial validators of a network
+//hash of the spec is previous hash of the first block and all nodes of a network verify it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GenesisSpec {
+    pub chain_id: String,
+    pub date: String,
+    pub allocations: Vec<Allocation>,
+    pub validators: Vec<GenesisValidator>,
+    //first block of new signing payloads and merkel tree, it is 1 when spec doesn't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade_height: Option<u64>,
+}
+
+//genesis record that is stored in database after applying the spec
+#[derive(Debug, Serialize, Deserialize)]
+struct GenesisRecord {
+    chain_id: String,
+    hash: String,
+}
+
+impl GenesisSpec {
+    //load genesis spec from a file, if there is no any file network doesn't have a genesis spec
+    pub fn load(path: &str) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Self>(&content) {
+                Ok(spec) => Ok(Some(spec)),
+                Err(e) => Err(format!("Genesis file is incorrect: {}", e)),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    //hash of genesis spec
+    pub fn hash(&self) -> String {
+        HashMaker::generate(&serde_json::to_string(self).unwrap())
+    }
+
+    //previous hash that the first block of chain should have
+    pub fn previous() -> String {
+        match GENESIS.as_ref() {
+            Some(spec) => spec.hash(),
+            None => LEGACY_GENESIS.to_string(),
+        }
+    }
+
+    //check genesis of database with genesis spec
+    //if database is empty genesis spec will be applied to it
+    pub async fn check(db: &Database) -> Result<(), &str> {
+        let spec = match GENESIS.as_ref() {
+            Some(spec) => spec,
+            None => return Ok(()),
+        };
+
+        let collection: Collection<Document> = db.collection("genesis");
+        match collection.find_one(doc! {}).await {
+            Ok(Some(doc)) => {
+                let record: GenesisRecord = from_document(doc).unwrap();
+                if record.hash == spec.hash() && record.chain_id == spec.chain_id {
+                    Ok(())
+                } else {
+                    Err("Genesis of database doesn't match the genesis spec!")
+                }
+            }
+            Ok(None) => {
+                let b_collection: Collection<Document> = db.collection("Blocks");
+                match b_collection.count_documents(doc! {}).await {
+                    Ok(0) => spec.apply(db).await,
+                    Ok(_) => Err("Database has blocks without any genesis!"),
+                    Err(_) => Err("Get count of blocks problem-(relay/practical/genesis 114)"),
+                }
+            }
+            Err(_) => Err("Querying genesis problem-(relay/practical/genesis 117)"),
+        }
+    }
+
+    //insert initial allocations as utxos and initial validators, then save genesis record
+    async fn apply<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        let hash = self.hash();
+
+        let mut utxos = Vec::new();
+        for (i, allocation) in self.allocations.iter().enumerate() {
+            let data = UnspentData {
+                wallet: allocation.wallet,
+                salt: i as u32,
+                value: allocation.value,
+            };
+            let unspent = Unspent {
+                hash: HashMaker::generate(&serde_json::to_string(&data).unwrap()),
+                data,
+            };
+            UTXO::generate(0, &hash, &hash, &unspent, db).await?;
+            utxos.push(UTXO::new(0, &hash, &hash, &unspent));
+        }
+
+        //commitment of utxo set at genesis is the first utxo root
+        UtxoCommitment::at(0, &utxos).save(db).await?;
+
+        let v_collection: Collection<Document> = db.collection("validators");
+        for (i, genesis_validator) in self.validators.iter().enumerate() {
+            let validator = Validator {
+                peerid: genesis_validator.peerid,
+                relay: genesis_validator.relay,
+                wallet: genesis_validator.wallet,
+                waiting: i as u64,
+            };
+            if v_collection
+                .insert_one(to_document(&validator).unwrap())
+                .await
+                .is_err()
+            {
+                return Err("Inserting genesis validator problem-(relay/practical/genesis 150)");
+            }
+        }
+
+        let record = GenesisRecord {
+            chain_id: self.chain_id.clone(),
+            hash,
+        };
+        let collection: Collection<Document> = db.collection("genesis");
+        match collection.insert_one(to_document(&record).unwrap()).await {
+            Ok(_) => {
+                info!("Genesis of {} applied: {}", record.chain_id, record.hash);
+                Ok(())
+            }
+            Err(_) => Err("Inserting genesis problem-(relay/practical/genesis 164)"),
+        }
+    }
+}
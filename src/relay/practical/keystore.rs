@@ -0,0 +1,221 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+};
+
+use bip39::{Language, Mnemonic};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use crossterm::{
+    event::{read, Event, KeyCode, KeyEvent, KeyEventKind},
+    terminal,
+};
+use once_cell::sync::OnceCell;
+use rand::RngCore;
+use scrypt::{scrypt, Params};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp_core::{
+    ed25519::{Pair, Public},
+    Pair as PairT,
+};
+
+use super::block::header::Sign;
+
+//version of keystore file format
+const VERSION: u8 = 1;
+//scrypt parameters of new keystores (N = 2^15, r = 8, p = 1)
+const LOG_N: u8 = 15;
+const R: u32 = 8;
+const P: u32 = 1;
+
+//unlocked wallet of relay that is set at start if relay has a keystore
+pub static WALLET_PAIR: OnceCell<Pair> = OnceCell::new();
+
+//parameters of scrypt that derives encryption key from passphrase
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: Vec<u8>,
+}
+
+//keystore keeps secret seed of relay's wallet encrypted by chacha20poly1305
+//with a key that is derived from passphrase by scrypt, public key is kept as plain text
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Keystore {
+    pub version: u8,
+    pub public: Public,
+    pub kdf: KdfParams,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    //make a new wallet with a BIP-39 mnemonic
+    pub fn generate() -> (Pair, String) {
+        let mnemonic = Mnemonic::generate_in(Language::English, 12).unwrap();
+        let phrase = mnemonic.to_string();
+        let (pair, _) = Pair::from_phrase(&phrase, None).unwrap();
+        (pair, phrase)
+    }
+
+    //recover a wallet from a seed15 (15 words) or BIP-39 mnemonic
+    pub fn from_mnemonic(phrase: &str) -> Result<Pair, String> {
+        let phrase = phrase.split_whitespace().collect::<Vec<&str>>().join(" ");
+        //seed15 wallets use sha256 of the seed as ed25519 secret seed
+        //15 words can be a BIP-39 phrase too, so it is checked as BIP-39 if it is not seed15
+        if let Ok(seed) = seed15::phrase::seed_phrase_to_seed(&phrase) {
+            let secret: [u8; 32] = Sha256::digest(seed).into();
+            return Ok(Pair::from_seed(&secret));
+        }
+        match Mnemonic::parse_in_normalized(Language::English, &phrase) {
+            Ok(_) => match Pair::from_phrase(&phrase, None) {
+                Ok((pair, _)) => Ok(pair),
+                Err(e) => Err(format!("BIP-39 phrase is incorrect: {:?}", e)),
+            },
+            Err(e) => Err(format!("Phrase is not a seed15 or BIP-39 phrase: {}", e)),
+        }
+    }
+
+    //derive encryption key from passphrase
+    fn key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; 32], String> {
+        let params = match Params::new(kdf.log_n, kdf.r, kdf.p, 32) {
+            Ok(params) => params,
+            Err(_) => return Err("Keystore scrypt parameters are incorrect.".to_string()),
+        };
+        let mut key = [0u8; 32];
+        match scrypt(passphrase.as_bytes(), &kdf.salt, &params, &mut key) {
+            Ok(_) => Ok(key),
+            Err(_) => Err("Deriving keystore key problem.".to_string()),
+        }
+    }
+
+    //encrypt secret seed of a wallet with a passphrase
+    pub fn encrypt(pair: &Pair, passphrase: &str) -> Result<Self, String> {
+        let mut salt = vec![0u8; 32];
+        let mut nonce = vec![0u8; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let kdf = KdfParams {
+            log_n: LOG_N,
+            r: R,
+            p: P,
+            salt,
+        };
+
+        let key = Self::key(passphrase, &kdf)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        match cipher.encrypt(Nonce::from_slice(&nonce), pair.seed().as_ref()) {
+            Ok(ciphertext) => Ok(Self {
+                version: VERSION,
+                public: pair.public(),
+                kdf,
+                nonce,
+                ciphertext,
+            }),
+            Err(_) => Err("Encrypting wallet problem.".to_string()),
+        }
+    }
+
+    //decrypt wallet of keystore with its passphrase
+    pub fn decrypt(&self, passphrase: &str) -> Result<Pair, String> {
+        if self.version != VERSION || self.nonce.len() != 12 {
+            return Err("Keystore version is not supported.".to_string());
+        }
+        let key = Self::key(passphrase, &self.kdf)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let seed = match cipher.decrypt(Nonce::from_slice(&self.nonce), self.ciphertext.as_ref()) {
+            Ok(seed) => seed,
+            Err(_) => return Err("Passphrase is wrong.".to_string()),
+        };
+        match Pair::from_seed_slice(&seed) {
+            Ok(pair) if pair.public() == self.public => Ok(pair),
+            _ => Err("Keystore is corrupted.".to_string()),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(keystore) => Ok(keystore),
+                Err(e) => Err(format!("Keystore file is incorrect: {}", e)),
+            },
+            Err(e) => Err(format!("Reading keystore {} problem: {}", path, e)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        match fs::write(path, serde_json::to_string_pretty(self).unwrap()) {
+            Ok(_) => {
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(path, fs::Permissions::from_mode(0o600)).ok();
+                }
+                Ok(())
+            }
+            Err(e) => Err(format!("Writing keystore {} problem: {}", path, e)),
+        }
+    }
+
+    //unlock keystore for signing with wallet of relay and return its public key
+    pub fn unlock(path: &str) -> Result<Public, String> {
+        let keystore = Self::load(path)?;
+        let passphrase = Self::passphrase("Keystore passphrase: ")?;
+        let pair = keystore.decrypt(&passphrase)?;
+        WALLET_PAIR.set(pair).ok();
+        Ok(keystore.public)
+    }
+
+    //passphrase comes from CENTICHAIN_PASSPHRASE for services or it is asked from terminal without echo
+    pub fn passphrase(prompt: &str) -> Result<String, String> {
+        if let Ok(passphrase) = env::var("CENTICHAIN_PASSPHRASE") {
+            return Ok(passphrase);
+        }
+
+        eprint!("{}", prompt);
+        io::stderr().flush().ok();
+        let mut passphrase = String::new();
+        if terminal::enable_raw_mode().is_err() {
+            //stdin is not a terminal
+            return match io::stdin().read_line(&mut passphrase) {
+                Ok(_) => Ok(passphrase.trim_end_matches(['\r', '\n']).to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+        }
+        let result = loop {
+            match read() {
+                Ok(Event::Key(KeyEvent {
+                    code,
+                    kind: KeyEventKind::Press,
+                    ..
+                })) => match code {
+                    KeyCode::Enter => break Ok(passphrase),
+                    KeyCode::Esc => break Err("Passphrase is canceled.".to_string()),
+                    KeyCode::Backspace => {
+                        passphrase.pop();
+                    }
+                    KeyCode::Char(c) => passphrase.push(c),
+                    _ => {}
+                },
+                Ok(_) => {}
+                Err(e) => break Err(e.to_string()),
+            }
+        };
+        terminal::disable_raw_mode().ok();
+        eprintln!();
+        result
+    }
+
+    //sign a payload with unlocked wallet of relay if it has a keystore
+    pub fn sign(payload: &str) -> Option<Sign> {
+        WALLET_PAIR.get().map(|pair| Sign {
+            signatgure: pair.sign(payload.as_bytes()),
+            key: pair.public(),
+        })
+    }
+}
@@ -6,17 +6,19 @@ use mongodb::{
     bson::{doc, Document},
     Collection, Database,
 };
+use rust_decimal::Decimal;
+use tracing::{info, warn};
 
-use crate::relay::{events::connections::ConnectionsHandler, tools::create_log::write_log};
+use crate::relay::{events::connections::ConnectionsHandler, tools::metrics::METRICS};
 
-use super::swarm::CentichainBehaviour;
+use super::{chain_params::CHAIN_PARAMS, stake::Stake, swarm::CentichainBehaviour};
 
 pub struct Leader {
     pub peerid: Option<PeerId>,
     pub timer: LeaderTime,
     pub time: Option<DateTime<Utc>>,
     pub in_check: bool,
-    pub votes: Vec<PeerId>,
+    pub votes: HashMap<PeerId, PeerId>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -43,7 +45,7 @@ impl Leader {
             timer: LeaderTime::Off,
             time: None,
             in_check: false,
-            votes: Vec::new(),
+            votes: HashMap::new(),
         }
     }
 
@@ -55,11 +57,15 @@ impl Leader {
     // start leader time for check its block
     pub fn timer_start(&mut self) {
         self.timer.start();
-        self.time.get_or_insert(Utc::now() + Duration::seconds(59));
+        self.time
+            .get_or_insert(Utc::now() + Duration::seconds(CHAIN_PARAMS.block_time));
     }
 
     //update to new leader
     pub fn update(&mut self, peerid: Option<PeerId>) {
+        if peerid.is_some() && peerid != self.peerid {
+            METRICS.leader_changed();
+        }
         self.timer.off();
         self.time = None;
         self.peerid = peerid;
@@ -90,14 +96,12 @@ impl Leader {
                             self.update(None);
                         }
 
-                        if self.peerid.is_some() {
-                            Ok(write_log(&format!(
-                                "Left leader remove as a wrongdoer: {}",
-                                self.peerid.unwrap()
-                            )))
+                        if let Some(peerid) = self.peerid {
+                            warn!("Left leader remove as a wrongdoer: {}", peerid);
                         } else {
-                            Ok(write_log("Leader is empty"))
+                            info!("Leader is empty");
                         }
+                        Ok(())
                     }
                     Err(_) => {
                         Err("Error during counting of validators-(relay/practical/leader 103)")
@@ -109,27 +113,43 @@ impl Leader {
     }
 
     //check votes and if it was quorum set it as leader
-    pub async fn check_votes<'a>(&mut self, db: &Database, vote: PeerId) -> Result<(), &'a str> {
+    //each validator has one vote that is weighted by its stake
+    pub async fn check_votes<'a>(
+        &mut self,
+        db: &Database,
+        voter: Option<PeerId>,
+        vote: PeerId,
+    ) -> Result<(), &'a str> {
         let collection: Collection<Document> = db.collection("validators");
-        //get count documents for knowing votes are upper than 50% of documents number or not
+        //get count documents for knowing there are other validators to vote or not
         match collection.count_documents(doc! {}).await {
             Ok(count) => {
                 if count > 1 {
-                    self.votes.push(vote);
+                    if let Some(voter) = voter {
+                        METRICS.leader_voted();
+                        self.votes.insert(voter, vote);
+                    }
 
-                    //if votes are upper than 50% of validators find most vote to set as leader
-                    if self.votes.len() >= ((count / 2) + 1) as usize {
-                        let mut hashmap_of_votes = HashMap::new(); //make hashmap to group by vote per peerid
-                        for v in self.votes.clone() {
-                            *hashmap_of_votes.entry(v).or_insert(0) += 1; //plus 1 if key is repetitive
+                    //sum weights of votes per candidate
+                    let weights = Stake::weights(db).await?;
+                    let total: Decimal = weights.values().sum();
+                    let mut voted = Decimal::ZERO;
+                    let mut candidates: HashMap<PeerId, Decimal> = HashMap::new();
+                    for (voter, candidate) in &self.votes {
+                        if let Some(weight) = weights.get(voter) {
+                            voted += weight;
+                            *candidates.entry(*candidate).or_insert(Decimal::ZERO) += weight;
                         }
-                        //get the most vote and change leader
-                        let result = hashmap_of_votes
+                    }
+
+                    //if votes have more than 50% of stakes set candidate with the most stake as leader
+                    if voted * Decimal::from(2) > total {
+                        let result = *candidates
                             .iter()
-                            .max_by_key(|v| *v)
+                            .max_by_key(|(peerid, weight)| (**weight, peerid.to_string()))
                             .unwrap()
-                            .0
-                            .clone();
+                            .0;
+                        self.votes.clear();
                         self.update(Some(result));
                         self.timer_start();
                     }
@@ -137,7 +157,6 @@ impl Leader {
                 } else {
                     self.update(Some(vote));
                     Ok(())
-
                 }
             }
             Err(_) => Err("Error while get count of validators' doc-(generator/leader 143)"),
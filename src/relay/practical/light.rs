@@ -0,0 +1,248 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use libp2p::{futures::StreamExt, PeerId};
+use mongodb::{
+    bson::{doc, from_document, Document},
+    options::FindOptions,
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::{ed25519::Public, Pair};
+
+use crate::relay::{practical::chain_params::CHAIN_PARAMS, tools::merkel::MerkelProof};
+
+use super::{
+    block::header::{Header, Sign},
+    finality::Checkpoint,
+};
+
+//maximum number of headers in a response
+pub const MAX_HEADERS: u64 = 500;
+
+//header without body, relay and date that light clients need for following the chain
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompactHeader {
+    pub number: u64,
+    pub hash: String,
+    pub previous: String,
+    pub merkel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utxo_root: Option<String>,
+    pub validator: PeerId,
+    pub signature: Sign,
+}
+
+//request of a range of headers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HeadersRequest {
+    pub from: u64,
+    pub count: u64,
+}
+
+//headers of a range with the last finalized checkpoint of relay
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadersResponse {
+    pub headers: Vec<CompactHeader>,
+    pub checkpoint: Option<Checkpoint>,
+    pub status: String,
+}
+
+impl From<&Header> for CompactHeader {
+    fn from(header: &Header) -> Self {
+        Self {
+            number: header.number,
+            hash: header.hash.clone(),
+            previous: header.previous.clone(),
+            merkel: header.merkel().to_string(),
+            utxo_root: header.utxo_root.clone(),
+            validator: header.validator,
+            signature: header.signature.clone(),
+        }
+    }
+}
+
+impl CompactHeader {
+    //check signature of validator over the header
+    pub fn verify_sign(&self) -> bool {
+        sp_core::ed25519::Pair::verify(
+            &self.signature.signatgure,
+            Header::signing_payload(
+                self.number,
+                &self.previous,
+                &self.merkel,
+                self.utxo_root.as_deref(),
+                &self.hash,
+            ),
+            &self.signature.key,
+        )
+    }
+
+    //check that a transaction is in block of this header
+    pub fn includes(&self, proof: &MerkelProof) -> bool {
+        CHAIN_PARAMS.upgraded(self.number) && proof.verify(&self.merkel)
+    }
+}
+
+impl HeadersRequest {
+    //get compact headers of range from database
+    pub async fn handle(&self, db: &Database) -> HeadersResponse {
+        let mut response = HeadersResponse {
+            headers: Vec::new(),
+            checkpoint: None,
+            status: String::new(),
+        };
+
+        let collection: Collection<Document> = db.collection("Blocks");
+        let to = self.from.saturating_add(self.count.min(MAX_HEADERS)) as i64;
+        let filter = doc! {"header.number": {"$gte": self.from as i64, "$lt": to}};
+        let options = FindOptions::builder()
+            .sort(doc! {"header.number": 1})
+            .projection(doc! {"header": 1})
+            .build();
+        match collection.find(filter).with_options(options).await {
+            Ok(mut cursor) => {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    let header: Header =
+                        from_document(doc.get_document("header").unwrap().clone()).unwrap();
+                    response.headers.push(CompactHeader::from(&header));
+                }
+            }
+            Err(_) => response.status = "Querying headers problem!".to_string(),
+        }
+
+        match Checkpoint::last(db).await {
+            Ok(checkpoint) => response.checkpoint = checkpoint,
+            Err(e) => response.status = e.to_string(),
+        }
+        response
+    }
+}
+
+//light client follows signed headers and finalized checkpoints without bodies of blocks
+//headers of registered validators are verified by their keys, other headers are verified
+//only when a checkpoint of the configured relays covers them
+pub struct LightClient {
+    headers: BTreeMap<u64, CompactHeader>,
+    validators: HashMap<PeerId, Public>,
+    relays: HashSet<PeerId>,
+    checkpoint: Option<Checkpoint>,
+}
+
+impl LightClient {
+    //relays are the relays that client trusts their checkpoints when more than 2/3 of them attest to one
+    pub fn new(relays: HashSet<PeerId>) -> Self {
+        Self {
+            headers: BTreeMap::new(),
+            validators: HashMap::new(),
+            relays,
+            checkpoint: None,
+        }
+    }
+
+    //keys of registered validators, for example validators of genesis spec
+    pub fn validators(mut self, validators: HashMap<PeerId, Public>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn tip(&self) -> Option<&CompactHeader> {
+        self.headers.values().next_back()
+    }
+
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    //attestations of more than 2/3 of configured relays make a checkpoint
+    fn quorum(&self) -> usize {
+        self.relays.len() * 2 / 3 + 1
+    }
+
+    //add headers that follow the tip of client with valid signatures
+    //a registered validator should sign its headers with its registered key
+    pub fn follow(&mut self, headers: Vec<CompactHeader>) -> Result<(), &'static str> {
+        for header in headers {
+            if let Some(tip) = self.tip() {
+                if header.number != tip.number + 1 || header.previous != tip.hash {
+                    return Err("Header doesn't follow the tip of light client.");
+                }
+            }
+            if !header.verify_sign() {
+                return Err("Header signature is incorrect.");
+            }
+            if let Some(key) = self.validators.get(&header.validator) {
+                if *key != header.signature.key {
+                    return Err("Header is not signed by the registered key of its validator.");
+                }
+            }
+            if let Some(checkpoint) = &self.checkpoint {
+                if checkpoint.number == header.number && checkpoint.hash != header.hash {
+                    return Err("Header conflicts with the finalized checkpoint.");
+                }
+            }
+            self.headers.insert(header.number, header);
+        }
+        Ok(())
+    }
+
+    //accept a newer checkpoint that a quorum of configured relays attested to it with valid signatures
+    pub fn finalize(&mut self, checkpoint: Checkpoint) -> Result<(), &'static str> {
+        if let Some(last) = &self.checkpoint {
+            if checkpoint.number <= last.number {
+                return Ok(());
+            }
+        }
+        let relays: HashSet<PeerId> = checkpoint
+            .attestations
+            .iter()
+            .filter(|a| {
+                a.number == checkpoint.number
+                    && a.hash == checkpoint.hash
+                    && self.relays.contains(&a.relay)
+                    && a.verify()
+            })
+            .map(|a| a.relay)
+            .collect();
+        if relays.len() < self.quorum() {
+            return Err("Checkpoint doesn't have attestations of a quorum of configured relays.");
+        }
+        if let Some(header) = self.headers.get(&checkpoint.number) {
+            if header.hash != checkpoint.hash {
+                return Err("Checkpoint conflicts with headers of light client.");
+            }
+        }
+        self.checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    //a header is verified if its validator is registered or a followed checkpoint covers it
+    //headers are linked by previous hashes, so the header of a checkpoint covers all of headers below it
+    pub fn is_verified(&self, number: u64) -> bool {
+        let covered = self.checkpoint.as_ref().is_some_and(|checkpoint| {
+            number <= checkpoint.number
+                && self
+                    .headers
+                    .get(&checkpoint.number)
+                    .is_some_and(|header| header.hash == checkpoint.hash)
+        });
+        match self.headers.get(&number) {
+            Some(header) => covered || self.validators.contains_key(&header.validator),
+            None => false,
+        }
+    }
+
+    //check a payment with its merkel proof against a verified header
+    pub fn verify_payment(&self, number: u64, proof: &MerkelProof) -> bool {
+        match self.headers.get(&number) {
+            Some(header) => self.is_verified(number) && header.includes(proof),
+            None => false,
+        }
+    }
+
+    //a block is final if it is at or below the last checkpoint
+    pub fn is_final(&self, number: u64) -> bool {
+        self.checkpoint
+            .as_ref()
+            .is_some_and(|checkpoint| number <= checkpoint.number)
+    }
+}
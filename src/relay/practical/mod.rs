@@ -6,3 +6,16 @@ pub mod validator;
 pub mod swarm;
 pub mod leader;
 pub mod reciept;
+pub mod finality;
+pub mod chain_params;
+pub mod config;
+pub mod genesis;
+pub mod stake;
+pub mod evidence;
+pub mod earnings;
+pub mod keystore;
+pub mod builder;
+pub mod coin_selection;
+pub mod light;
+pub mod commitment;
+pub mod reindex;
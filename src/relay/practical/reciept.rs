@@ -7,7 +7,10 @@ use rust_decimal::Decimal;
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
 
-use super::{block::coinbase::Coinbase, transaction::Transaction};
+use super::{
+    block::{block::Block, coinbase::Coinbase},
+    transaction::Transaction,
+};
 
 #[serde_as]
 #[derive(Debug, Serialize)]
@@ -159,4 +162,32 @@ impl Reciept {
             Err(_) => Err("Error while updating reciept-(relay/practical/reciept 159)"),
         }
     }
+
+    //eviction will use when operator removed a pending transaction from mempool
+    pub async fn eviction<'a>(db: &'a Database, hash: &String) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("reciepts");
+        let filter = doc! {"hash": hash, "status": "Pending"};
+        let update = doc! {"$set": {"status": "Evicted".to_string(), "description": "It was removed from mempool of relay".to_string()}};
+
+        match collection.update_one(filter, update).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Error while updating reciept-(relay/practical/reciept 174)"),
+        }
+    }
+
+    //finalization will use after a checkpoint made and reciepts of the finalized block will be finalized
+    pub async fn finalization<'a>(db: &'a Database, block: &Block) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("reciepts");
+        let mut hashes = vec![block.body.coinbase.hash.clone()];
+        for transaction in &block.body.transactions {
+            hashes.push(transaction.hash.clone());
+        }
+        let filter = doc! {"hash": {"$in": hashes}, "status": "Confirmed"};
+        let update = doc! {"$set": {"status": "Finalized".to_string(), "description": "It was placed in a finalized checkpoint".to_string()}};
+
+        match collection.update_many(filter, update).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Error while finalizing reciepts-(relay/practical/reciept 190)"),
+        }
+    }
 }
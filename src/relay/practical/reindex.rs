@@ -0,0 +1,303 @@
+use std::collections::HashSet;
+
+use libp2p::{futures::StreamExt, PeerId};
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    options::{FindOneOptions, FindOptions},
+    Collection, Database,
+};
+use tracing::instrument;
+
+use super::{
+    block::block::Block, commitment::UtxoCommitment, genesis::GenesisSpec,
+    reciept::Reciept, relay::RelayWallet, validator::Validator,
+};
+
+//database that chain is rebuilt in it
+pub const REINDEX_DB: &str = "Centichain_reindex";
+//number of blocks between progress reports
+const PROGRESS_INTERVAL: u64 = 1000;
+
+//result of reindexing chain of relay
+#[derive(Debug)]
+pub struct ReindexReport {
+    pub blocks: u64,
+    //first block that failed validation with its error
+    pub failed: Option<(u64, String)>,
+    //first block that its utxo root differs from the imported one
+    pub root_mismatch: Option<u64>,
+    pub utxos_match: bool,
+    //registrations of imported validators are same as the rebuilt ones, that genesis made or were copied
+    pub validators_match: bool,
+    pub missing_reciepts: usize,
+    pub extra_reciepts: usize,
+}
+
+pub struct Reindex;
+
+impl Reindex {
+    //walk blocks of relay from genesis through block validation into a fresh database
+    //then compare rebuilt utxos and reciepts with the imported ones
+    //it continues from the last rebuilt block unless fresh is true
+    pub async fn run<'a>(
+        source: &'a Database,
+        target: &'a Database,
+        fresh: bool,
+        progress: impl Fn(u64, u64),
+    ) -> Result<ReindexReport, &'a str> {
+        if fresh && target.drop().await.is_err() {
+            return Err("Dropping reindex database problem-(relay/practical/reindex 46)");
+        }
+
+        let mut last_block = Self::last_block(target).await?;
+        if last_block.is_empty() {
+            Self::prepare(source, target).await?;
+        }
+        let from = last_block.first().map(|b| b.header.number).unwrap_or(0);
+
+        let blocks_collection: Collection<Document> = source.collection("Blocks");
+        let total = match blocks_collection.count_documents(doc! {}).await {
+            Ok(count) => count,
+            Err(_) => return Err("Get count of blocks problem-(relay/practical/reindex 58)"),
+        };
+        let options = FindOptions::builder()
+            .sort(doc! {"header.number": 1})
+            .build();
+        let mut cursor = match blocks_collection
+            .find(doc! {"header.number": {"$gt": from as i64}})
+            .with_options(options)
+            .await
+        {
+            Ok(cursor) => cursor,
+            Err(_) => return Err("Querying blocks problem-(relay/practical/reindex 69)"),
+        };
+
+        let mut report = ReindexReport {
+            blocks: from,
+            failed: None,
+            root_mismatch: None,
+            utxos_match: false,
+            validators_match: false,
+            missing_reciepts: 0,
+            extra_reciepts: 0,
+        };
+        while let Some(doc) = cursor.next().await {
+            let block: Block = match doc.map(from_document) {
+                Ok(Ok(block)) => block,
+                _ => return Err("Reading blocks problem-(relay/practical/reindex 87)"),
+            };
+            let number = block.header.number;
+            if let Err(e) = Self::apply(&block, &mut last_block, target).await {
+                report.failed = Some((number, e.to_string()));
+                break;
+            }
+
+            //utxo roots of imported chain should be same as rebuilt ones
+            if report.root_mismatch.is_none() {
+                if let (Some(imported), Some(rebuilt)) = (
+                    UtxoCommitment::get(source, number).await?,
+                    UtxoCommitment::get(target, number).await?,
+                ) {
+                    if imported.root != rebuilt.root {
+                        report.root_mismatch = Some(number);
+                    }
+                }
+            }
+
+            report.blocks = number;
+            if number.is_multiple_of(PROGRESS_INTERVAL) {
+                progress(number, total);
+            }
+        }
+
+        if report.failed.is_none() {
+            let imported = UtxoCommitment::of(&UtxoCommitment::chain_utxos(source).await?);
+            let rebuilt = UtxoCommitment::of(&UtxoCommitment::chain_utxos(target).await?);
+            report.utxos_match = imported == rebuilt;
+
+            report.validators_match =
+                Self::registrations(source).await? == Self::registrations(target).await?;
+
+            let imported = Self::reciepts(source).await?;
+            let rebuilt = Self::reciepts(target).await?;
+            report.missing_reciepts = rebuilt.difference(&imported).count();
+            report.extra_reciepts = imported.difference(&rebuilt).count();
+        }
+        Ok(report)
+    }
+
+    //last rebuilt block
+    async fn last_block(db: &Database) -> Result<Vec<Block>, &str> {
+        let collection: Collection<Document> = db.collection("Blocks");
+        let options = FindOneOptions::builder()
+            .sort(doc! {"header.number": -1})
+            .build();
+        match collection.find_one(doc! {}).with_options(options).await {
+            Ok(Some(doc)) => match from_document(doc) {
+                Ok(block) => Ok(vec![block]),
+                Err(_) => Err("Reading last block problem-(relay/practical/reindex 138)"),
+            },
+            Ok(None) => Ok(Vec::new()),
+            Err(_) => Err("Querying last block problem-(relay/practical/reindex 141)"),
+        }
+    }
+
+    //apply genesis to the fresh database and copy validators that are registered in the network
+    //and wallets of relays, because they are not in blocks and headers and coinbases need them
+    //validators of genesis are kept as genesis made them, so imported ones are compared with them at the end
+    async fn prepare<'a>(source: &'a Database, target: &'a Database) -> Result<(), &'a str> {
+        GenesisSpec::check(target).await?;
+
+        for validator in Self::validators(source).await? {
+            Self::copy(target, "validators", &validator.peerid, to_document(&validator).unwrap()).await?;
+        }
+
+        let collection: Collection<Document> = source.collection("relays");
+        let mut cursor = match collection.find(doc! {}).await {
+            Ok(cursor) => cursor,
+            Err(_) => return Err("Querying relays problem-(relay/practical/reindex 158)"),
+        };
+        while let Some(doc) = cursor.next().await {
+            let relay: RelayWallet = match doc.map(from_document) {
+                Ok(Ok(relay)) => relay,
+                _ => return Err("Reading relays problem-(relay/practical/reindex 163)"),
+            };
+            Self::copy(target, "relays", &relay.peerid, to_document(&relay).unwrap()).await?;
+        }
+        Ok(())
+    }
+
+    //insert a record of a peer if the collection doesn't have it yet
+    async fn copy<'a>(
+        target: &'a Database,
+        name: &str,
+        peerid: &PeerId,
+        record: Document,
+    ) -> Result<(), &'a str> {
+        let collection: Collection<Document> = target.collection(name);
+        let filter = doc! {"peerid": peerid.to_string()};
+        match collection
+            .update_one(filter, doc! {"$setOnInsert": record})
+            .upsert(true)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Copying records of peers problem-(relay/practical/reindex 185)"),
+        }
+    }
+
+    //registered validators of a database
+    async fn validators(db: &Database) -> Result<Vec<Validator>, &str> {
+        let collection: Collection<Document> = db.collection("validators");
+        let mut cursor = match collection.find(doc! {}).await {
+            Ok(cursor) => cursor,
+            Err(_) => return Err("Querying validators problem-(relay/practical/reindex 194)"),
+        };
+        let mut validators = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            match doc.map(from_document) {
+                Ok(Ok(validator)) => validators.push(validator),
+                _ => return Err("Reading validators problem-(relay/practical/reindex 200)"),
+            }
+        }
+        Ok(validators)
+    }
+
+    //validators as peerid, relay and wallet that are registered with
+    async fn registrations(db: &Database) -> Result<HashSet<String>, &str> {
+        Ok(Self::validators(db)
+            .await?
+            .iter()
+            .map(|v| format!("{}:{}:{}", v.peerid, v.relay, v.wallet))
+            .collect())
+    }
+
+    //validate a block like a received one, then insert it with its reciepts
+    #[instrument(name = "block", skip_all, fields(number = block.header.number, hash = %block.header.hash))]
+    async fn apply<'a>(
+        block: &Block,
+        last_block: &mut Vec<Block>,
+        db: &'a Database,
+    ) -> Result<(), &'a str> {
+        block
+            .validation(last_block, db)
+            .await
+            .map_err(|e| e.message())?;
+
+        let collection: Collection<Document> = db.collection("Blocks");
+        if collection
+            .insert_one(to_document(block).unwrap())
+            .await
+            .is_err()
+        {
+            return Err("Inserting block problem-(relay/practical/reindex 233)");
+        }
+
+        let number = block.header.number;
+        Reciept::insertion(Some(number), None, Some(&block.body.coinbase), db).await?;
+        for transaction in &block.body.transactions {
+            Reciept::insertion(Some(number), Some(transaction), None, db).await?;
+            Reciept::confirmation(db, &transaction.hash, &number).await?;
+        }
+
+        last_block.clear();
+        last_block.push(block.clone());
+        Ok(())
+    }
+
+    //reciepts of blocks as hash, receiver and value
+    async fn reciepts(db: &Database) -> Result<HashSet<String>, &str> {
+        let collection: Collection<Document> = db.collection("reciepts");
+        let filter = doc! {"status": {"$ne": "Pending"}};
+        match collection.find(filter).await {
+            Ok(mut cursor) => {
+                let mut reciepts = HashSet::new();
+                while let Some(doc) = cursor.next().await {
+                    let doc = match doc {
+                        Ok(doc) => doc,
+                        Err(_) => return Err("Reading reciepts problem-(relay/practical/reindex 258)"),
+                    };
+                    reciepts.insert(format!(
+                        "{}:{}:{}",
+                        doc.get_str("hash").unwrap_or_default(),
+                        doc.get_str("to").unwrap_or_default(),
+                        doc.get_str("value").unwrap_or_default()
+                    ));
+                }
+                Ok(reciepts)
+            }
+            Err(_) => Err("Querying reciepts problem-(relay/practical/reindex 269)"),
+        }
+    }
+
+    //replace collections of relay with the rebuilt ones after a complete reindex
+    //pending block and mempool belong to the replaced state, so they are cleared
+    pub async fn promote<'a>(
+        source: &'a Database,
+        target: &'a Database,
+    ) -> Result<Vec<String>, &'a str> {
+        let names = match target.list_collection_names().await {
+            Ok(names) => names,
+            Err(_) => return Err("Listing rebuilt collections problem-(relay/practical/reindex 281)"),
+        };
+        let admin = source.client().database("admin");
+        for name in &names {
+            let command = doc! {
+                "renameCollection": format!("{}.{}", target.name(), name),
+                "to": format!("{}.{}", source.name(), name),
+                "dropTarget": true,
+            };
+            if admin.run_command(command).await.is_err() {
+                return Err("Promoting rebuilt collection problem-(relay/practical/reindex 291)");
+            }
+        }
+
+        for name in ["pending_block", "transactions"] {
+            let collection: Collection<Document> = source.collection(name);
+            if collection.delete_many(doc! {}).await.is_err() {
+                return Err("Clearing replaced state problem-(relay/practical/reindex 298)");
+            }
+        }
+        Ok(names)
+    }
+}
@@ -1,7 +1,20 @@
-use libp2p::PeerId;
+use libp2p::{PeerId, Swarm};
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    Collection, Database,
+};
 use rand::seq::IteratorRandom;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sp_core::ed25519::Public;
+use tracing::error;
+
+use crate::relay::{events::gossip_messages::GossipMessages, tools::metrics::METRICS};
+
+use super::{
+    chain_params::{Network, CHAIN_PARAMS},
+    swarm::CentichainBehaviour,
+};
 
 //this structure is for knowing that relay is first in the network or not
 #[derive(Debug)]
@@ -61,6 +74,10 @@ impl RelayStruct {
             .position(|relay| relay == self)
             .unwrap();
         dialed_relays.relays.remove(index);
+        //devnet relays are not in Centichain server
+        if CHAIN_PARAMS.network == Network::Devnet {
+            return Ok(());
+        }
         //post relay address and ip to Centichain server for remove these from server
         let client = Client::new();
         match client
@@ -87,3 +104,68 @@ impl RelayStruct {
         }
     }
 }
+
+// =====================================================================
+
+//relay wallet binds peerid of a relay to its wallet for paying relay's fee in coinbase
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RelayWallet {
+    pub peerid: PeerId,
+    pub wallet: Public,
+}
+
+impl RelayWallet {
+    pub fn new(peerid: PeerId, wallet: Public) -> Self {
+        Self { peerid, wallet }
+    }
+
+    //insert or replace wallet of relay in relays collection
+    pub async fn save<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("relays");
+        let filter = doc! {"peerid": self.peerid.to_string()};
+        match collection
+            .replace_one(filter, to_document(self).unwrap())
+            .upsert(true)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Saving relay's wallet problem-(relay/practical/relay 121)"),
+        }
+    }
+
+    //get wallet of a relay by its peerid
+    pub async fn get<'a>(db: &'a Database, peerid: &PeerId) -> Result<Option<Public>, &'a str> {
+        let collection: Collection<Document> = db.collection("relays");
+        match collection
+            .find_one(doc! {"peerid": peerid.to_string()})
+            .await
+        {
+            Ok(opt) => Ok(opt.map(|doc| from_document::<Self>(doc).unwrap().wallet)),
+            Err(_) => Err("Querying relay's wallet problem-(relay/practical/relay 133)"),
+        }
+    }
+
+    //gossip wallet of this relay to other relays
+    pub fn announce(&self, swarm: &mut Swarm<CentichainBehaviour>) {
+        let gossip_message = GossipMessages::RelayWallet(self.clone());
+        let str_gossip_message = serde_json::to_string(&gossip_message).unwrap();
+        if let Err(e) = swarm
+            .behaviour_mut()
+            .gossipsub
+            .publish(CentichainBehaviour::topic("relay"), str_gossip_message)
+        {
+            METRICS.gossip_failed("relay");
+            error!("Gossiping relay's wallet problem: {}", e);
+        }
+    }
+
+    //handle wallet of a relay that recieved from gossip
+    //only the relay itself can announce its wallet
+    pub async fn handle<'a>(&self, db: &'a Database, source: Option<PeerId>) -> Result<(), &'a str> {
+        if source == Some(self.peerid) {
+            self.save(db).await
+        } else {
+            Ok(())
+        }
+    }
+}
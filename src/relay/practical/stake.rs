@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use libp2p::{futures::StreamExt, PeerId};
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    Collection, Database,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sp_core::ed25519::Public;
+use tracing::warn;
+
+use crate::relay::tools::utxo::UTXO;
+
+use super::{
+    chain_params::CHAIN_PARAMS,
+    transaction::{Script, Transaction, TransactionError, Unspent, UnspentData},
+    validator::Validator,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum StakeStatus {
+    Bonded,
+    Unbonding,
+    Slashed,
+}
+
+//a stake is output of a stake transaction that is locked as bond of a validator
+//it returns to wallet as utxo after unbonding delay, unless it is slashed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Stake {
+    pub validator: PeerId,
+    pub wallet: Public,
+    pub utxo: UTXO,
+    pub status: StakeStatus,
+    pub release: Option<u64>,
+}
+
+impl Stake {
+    //bond of a stake transaction is its first output that goes to the signer with value of transaction
+    pub fn script_validation<'a>(trx: &Transaction) -> Result<(), &'a str> {
+        match trx.script {
+            Script::Stake(_) => match trx.output.unspents.first() {
+                Some(bond)
+                    if bond.data.wallet == *trx.signer()
+                        && bond.data.value == trx.value
+                        && trx.value >= CHAIN_PARAMS.min_stake =>
+                {
+                    Ok(())
+                }
+                _ => Err("Transaction is incorrect.(stake problem!)"),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    //an unbond transaction needs a bonded stake of its signer for the validator
+    pub async fn unbond_validation<'a>(
+        trx: &Transaction,
+        db: &Database,
+    ) -> Result<(), TransactionError<'a>> {
+        match &trx.script {
+            Script::Unbond(validator) => {
+                let filter = doc! {
+                    "validator": validator.to_string(),
+                    "wallet": trx.signer().to_string(),
+                    "status": "Bonded",
+                };
+                let collection: Collection<Document> = db.collection("stakes");
+                match collection.count_documents(filter).await {
+                    Ok(0) => Err(TransactionError::Invalid("Transaction is incorrect.(unbond problem!)")),
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(TransactionError::Query("Querying stakes problem-(relay/practical/stake 73)")),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    //apply staking script of a transaction in a block
+    //returns true if first output of transaction is locked as stake and shouldn't be a utxo
+    pub async fn apply<'a>(trx: &Transaction, number: u64, db: &Database) -> Result<bool, &'a str> {
+        let collection: Collection<Document> = db.collection("stakes");
+        match &trx.script {
+            Script::Stake(validator) => {
+                let bond = &trx.output.unspents[0];
+                let stake = Self {
+                    validator: *validator,
+                    wallet: bond.data.wallet,
+                    utxo: UTXO {
+                        block: number,
+                        trx_hash: trx.hash.clone(),
+                        output_hash: trx.output.hash.clone(),
+                        unspent_hash: bond.hash.clone(),
+                        unspent: bond.data.value,
+                    },
+                    status: StakeStatus::Bonded,
+                    release: None,
+                };
+                //bond is inserted once, so applying the block again doesn't bond it twice
+                let filter = doc! {"utxo.unspent_hash": &bond.hash};
+                match collection
+                    .update_one(filter, doc! {"$setOnInsert": to_document(&stake).unwrap()})
+                    .upsert(true)
+                    .await
+                {
+                    Ok(_) => Ok(true),
+                    Err(_) => Err("Inserting stake problem-(relay/practical/stake 108)"),
+                }
+            }
+            Script::Unbond(validator) => {
+                let filter = doc! {
+                    "validator": validator.to_string(),
+                    "wallet": trx.signer().to_string(),
+                    "status": "Bonded",
+                };
+                let release = (number + CHAIN_PARAMS.unbonding_delay) as i64;
+                let update = doc! {"$set": {"status": "Unbonding", "release": release}};
+                match collection.update_many(filter, update).await {
+                    Ok(_) => Ok(false),
+                    Err(_) => Err("Updating stakes problem-(relay/practical/stake 121)"),
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
+    //stakes that their unbonding delay is finished at a block
+    pub async fn releasable<'a>(number: u64, db: &Database) -> Result<Vec<Self>, &'a str> {
+        let collection: Collection<Document> = db.collection("stakes");
+        let filter = doc! {"status": "Unbonding", "release": {"$lte": number as i64}};
+        let mut stakes = Vec::new();
+        match collection.find(filter).await {
+            Ok(mut cursor) => {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    stakes.push(from_document::<Self>(doc).unwrap());
+                }
+                Ok(stakes)
+            }
+            Err(_) => Err("Querying stakes problem-(relay/practical/stake 140)"),
+        }
+    }
+
+    //return stakes that their unbonding delay is finished to their wallets as utxo
+    pub async fn release<'a>(number: u64, db: &Database) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("stakes");
+        let filter = doc! {"status": "Unbonding", "release": {"$lte": number as i64}};
+        for stake in Self::releasable(number, db).await? {
+            let unspent = Unspent {
+                hash: stake.utxo.unspent_hash.clone(),
+                data: UnspentData {
+                    wallet: stake.wallet,
+                    salt: 0,
+                    value: stake.utxo.unspent,
+                },
+            };
+            UTXO::generate(
+                stake.utxo.block,
+                &stake.utxo.trx_hash,
+                &stake.utxo.output_hash,
+                &unspent,
+                db,
+            )
+            .await?;
+        }
+
+        match collection.delete_many(filter).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Deleting released stakes problem-(relay/practical/stake 169)"),
+        }
+    }
+
+    //bonded amount of a validator
+    pub async fn bonded<'a>(db: &Database, validator: &PeerId) -> Result<Decimal, &'a str> {
+        let collection: Collection<Document> = db.collection("stakes");
+        let filter = doc! {"validator": validator.to_string(), "status": "Bonded"};
+        match collection.find(filter).await {
+            Ok(mut cursor) => {
+                let mut amount = Decimal::ZERO;
+                while let Some(Ok(doc)) = cursor.next().await {
+                    amount += from_document::<Self>(doc).unwrap().utxo.unspent;
+                }
+                Ok(amount)
+            }
+            Err(_) => Err("Querying stakes problem-(relay/practical/stake 185)"),
+        }
+    }
+
+    //weight of each validator in leader selection is its bonded stake
+    //validators with less than minimum stake don't have weight, unless no validator has minimum stake
+    //(like genesis validators of a new network) and then all of validators have equal weights
+    pub async fn weights<'a>(db: &Database) -> Result<HashMap<PeerId, Decimal>, &'a str> {
+        let collection: Collection<Document> = db.collection("validators");
+        let mut bonds = HashMap::new();
+        match collection.find(doc! {}).await {
+            Ok(mut cursor) => {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    let validator: Validator = from_document(doc).unwrap();
+                    let bonded = Self::bonded(db, &validator.peerid).await?;
+                    bonds.insert(validator.peerid, bonded);
+                }
+            }
+            Err(_) => return Err("Querying validators problem-(relay/practical/stake 203)"),
+        }
+
+        let weights: HashMap<PeerId, Decimal> = bonds
+            .iter()
+            .filter(|(_, bonded)| **bonded >= CHAIN_PARAMS.min_stake)
+            .map(|(peerid, bonded)| (*peerid, *bonded))
+            .collect();
+        if weights.is_empty() {
+            Ok(bonds.into_keys().map(|peerid| (peerid, Decimal::ONE)).collect())
+        } else {
+            Ok(weights)
+        }
+    }
+
+    //burn stakes of a validator that its misbehaviour is proved
+    pub async fn slash<'a>(db: &Database, validator: &PeerId) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("stakes");
+        let filter = doc! {
+            "validator": validator.to_string(),
+            "status": {"$in": ["Bonded", "Unbonding"]},
+        };
+        let update = doc! {"$set": {"status": "Slashed", "release": null}};
+        match collection.update_many(filter, update).await {
+            Ok(result) => {
+                if result.modified_count > 0 {
+                    warn!("Stakes of {} slashed", validator);
+                }
+                Ok(())
+            }
+            Err(_) => Err("Slashing stakes problem-(relay/practical/stake 233)"),
+        }
+    }
+}
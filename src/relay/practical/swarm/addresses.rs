@@ -5,10 +5,14 @@ use libp2p::{Multiaddr, Swarm};
 use rand::seq::SliceRandom;
 
 use serde::Deserialize;
+use tracing::{debug, info, warn};
 
 use crate::relay::{
-    practical::relay::{DialedRelays, First, RelayStruct},
-    tools::create_log::write_log,
+    practical::{
+        chain_params::{Network, CHAIN_PARAMS},
+        config::CONFIG,
+        relay::{DialedRelays, First, RelayStruct},
+    },
 };
 
 use super::CentichainBehaviour;
@@ -49,19 +53,35 @@ impl Addresses {
     pub async fn contact<'a>(
         swarm: &mut Swarm<CentichainBehaviour>,
     ) -> Result<DialedRelays, &'a str> {
+        //dial configured bootstrap relays instead of getting relays from Centichain server
+        if !CONFIG.bootstrap.is_empty() {
+            let relays = CONFIG
+                .bootstrap
+                .iter()
+                .map(|addr| RelayStruct::new(None, String::new(), addr.clone()))
+                .collect();
+            return Self::contacting(relays, swarm).await;
+        }
+
+        //a devnet relay without bootstrap relays is the first relay of its network
+        if CHAIN_PARAMS.network == Network::Devnet {
+            info!("You Are First Node In The Devnet, Welcome:)");
+            return Ok(DialedRelays::new(First::Yes, Vec::new()));
+        }
+
         //check internet connection and if it connection is stable then start dial with relays as random
-        write_log("Check your internet...");
+        warn!("Check your internet...");
         let internet_connection = TcpStream::connect("8.8.8.8:53");
 
         if internet_connection.is_ok() {
-            write_log("Your internet is connected");
+            info!("Your internet is connected");
             //check count of relays and if there are any relays in the network then start dialing to a random relay
-            write_log("Checking for relays...");
+            info!("Checking for relays...");
             match Self::get().await {
                 Ok(relays) => Self::contacting(relays, swarm).await,
                 Err(e) => {
                     if e == "first" {
-                        write_log("You Are First Node In The Centichain Network, Welcome:)");
+                        info!("You Are First Node In The Centichain Network, Welcome:)");
                         let dialed_relays = DialedRelays::new(First::Yes, Vec::new());
                         Ok(dialed_relays)
                     } else {
@@ -78,7 +98,7 @@ impl Addresses {
         relays: Vec<RelayStruct>,
         swarm: &mut Swarm<CentichainBehaviour>,
     ) -> Result<DialedRelays, &'a str> {
-        write_log("Relays found, Start dialing...");
+        info!("Relays found, Start dialing...");
         //choos 6 relays as random for dialing
         let mut random_relays: Vec<RelayStruct> = Vec::new();
         if relays.len() > 10 {
@@ -102,7 +122,7 @@ impl Addresses {
                     is_err.get_or_insert("Dialing Error!");
                 }
             }
-            write_log(&format!("Dialing with: {}", relay.addr))
+            debug!("Dialing with: {}", relay.addr)
         }
 
         if is_err.is_none() {
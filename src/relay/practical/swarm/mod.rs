@@ -1,8 +1,9 @@
 mod addresses;
-use std::{pin::Pin, time::Duration};
+use std::{fs, pin::Pin, time::Duration};
 
 use addresses::Addresses;
 use libp2p::{
+    allow_block_list::{self, BlockedPeers},
     gossipsub::{Config, IdentTopic, MessageAuthenticity},
     identity::Keypair,
     request_response::{cbor, ProtocolSupport},
@@ -11,7 +12,7 @@ use libp2p::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::relay::DialedRelays;
+use super::{chain_params::CHAIN_PARAMS, config::CONFIG, relay::DialedRelays};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Req {
@@ -33,17 +34,31 @@ impl Res {
 pub struct CentichainBehaviour {
     pub gossipsub: libp2p::gossipsub::Behaviour,
     pub reqres: cbor::Behaviour<Req, Res>,
+    //peers that operator banned, their connections are denied
+    pub blocked: allow_block_list::Behaviour<BlockedPeers>,
 }
 
 impl CentichainBehaviour {
-    pub async fn new() -> (Pin<Box<Swarm<CentichainBehaviour>>>, PeerId) {
-        //generate keypair for get peerid to comunicate in the network
-        let keypair = Keypair::generate_ed25519();
+    //load keypair from file if it was configured (devnet relays have fixed peerids)
+    //else generate keypair for get peerid to comunicate in the network
+    pub fn keypair() -> Result<Keypair, String> {
+        match &CONFIG.keypair_path {
+            Some(path) => match fs::read(path) {
+                Ok(bytes) => Keypair::from_protobuf_encoding(&bytes)
+                    .map_err(|_| format!("Keypair file {} is not a valid keypair", path)),
+                Err(e) => Err(format!("Reading keypair file {} problem: {}", path, e)),
+            },
+            None => Ok(Keypair::generate_ed25519()),
+        }
+    }
+
+    pub async fn new() -> Result<(Pin<Box<Swarm<CentichainBehaviour>>>, PeerId, Keypair), String> {
+        let keypair = Self::keypair()?;
         let peerid = PeerId::from_public_key(&keypair.public());
 
         //topic for subscribing to a gossipsub protocol group
-        let r_topic = IdentTopic::new("relay");
-        let v_topic = IdentTopic::new("validator");
+        let r_topic = Self::topic("relay");
+        let v_topic = Self::topic("validator");
 
         //configure gossipsub protocol
         let auth = MessageAuthenticity::Signed(keypair.clone());
@@ -54,18 +69,22 @@ impl CentichainBehaviour {
 
         //configure request response protocol
         let reqres = cbor::Behaviour::<Req, Res>::new(
-            [(StreamProtocol::new("/mg/1.0"), ProtocolSupport::Full)],
+            [(Self::protocol(), ProtocolSupport::Full)],
             libp2p::request_response::Config::default(),
         );
 
         //swarm behaviour
-        let behaviour = CentichainBehaviour { gossipsub, reqres };
+        let behaviour = CentichainBehaviour {
+            gossipsub,
+            reqres,
+            blocked: Default::default(),
+        };
 
         //it will configure swarm to has stable connection without time limitation
         let swarmconf = libp2p::swarm::Config::with_tokio_executor()
             .with_idle_connection_timeout(Duration::from_secs(u64::MAX));
         //build new swarm with tcp, websocket, quic protocols with centichain behaviour(gossipsub & reqres) and tokio async runtime
-        let mut swarm = SwarmBuilder::with_existing_identity(keypair)
+        let mut swarm = SwarmBuilder::with_existing_identity(keypair.clone())
             .with_tokio()
             .with_tcp(
                 Default::default(),
@@ -84,13 +103,21 @@ impl CentichainBehaviour {
             .with_swarm_config(|_config| swarmconf)
             .build();
 
-        //listen on a random port on all IPs in a system
-        swarm
-            .listen_on("/ip4/0.0.0.0/tcp/0".parse().unwrap())
-            .unwrap();
+        //listen on configured address that is a random port on all IPs in a system by default
+        swarm.listen_on(CONFIG.listen.parse().unwrap()).unwrap();
+
+        //return a tuple that has a pined swarm on heap, a peerid and keypair of relay for signing
+        Ok((Box::pin(swarm), peerid, keypair))
+    }
+
+    //gossipsub topic of this network, chain id is a part of topic to separate networks
+    pub fn topic(name: &str) -> IdentTopic {
+        IdentTopic::new(format!("{}/{}", CHAIN_PARAMS.chain_id, name))
+    }
 
-        //return a tuple that has a pined swarm on heap and a peerid
-        (Box::pin(swarm), peerid)
+    //request response protocol of this network
+    pub fn protocol() -> StreamProtocol {
+        StreamProtocol::try_from_owned(format!("/mg/{}/1.0", CHAIN_PARAMS.chain_id)).unwrap()
     }
 
     //dialing to realys as random
@@ -1,25 +1,32 @@
 use chrono::Utc;
-use libp2p::Swarm;
+use libp2p::{PeerId, Swarm};
 use mongodb::{
-    bson::{doc, to_document, Document},
+    bson::{doc, from_document, to_document, Document},
     Collection, Database,
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use sp_core::{ed25519::Public, Pair};
+use tracing::instrument;
 
 use crate::relay::{
     events::connections::ConnectionsHandler,
-    tools::{utxo::UTXO, HashMaker, MerkelRoot},
+    tools::{merkel::MerkelRoot, metrics::METRICS, utxo::UTXO, HashMaker},
 };
 
 use super::{
     block::header::Sign,
+    chain_params::CHAIN_PARAMS,
     leader::{Leader, LeaderTime},
+    reciept::Reciept,
+    stake::Stake,
     swarm::CentichainBehaviour,
 };
 
+// Maximum number of decimal places that amounts of a transaction can have
+pub const MAX_DECIMALS: u32 = 12;
+
 // Define a transaction in the Centichain network
 // The hash of the transaction is derived from the hashes of its inputs and outputs
 #[serde_as]
@@ -37,12 +44,44 @@ pub struct Transaction {
     pub date: String,
 }
 
+// Define the reasons that a transaction can be rejected for
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TransactionError<'a> {
+    // The transaction breaks a rule of the chain, like its hashes, signature, values, stake or inputs
+    Invalid(&'a str),
+    // This relay couldn't check the transaction, like a problem of its database
+    Query(&'a str),
+}
+
+impl<'a> TransactionError<'a> {
+    pub fn message(&self) -> &'a str {
+        match self {
+            Self::Invalid(e) | Self::Query(e) => e,
+        }
+    }
+}
+
 // Define a script for highlighting the transaction's signature
 // It can have either a single signature or multiple signatures
+// Stake locks the first output as bond of a validator and Unbond starts unbonding of it
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Script {
     Single,
     Multi,
+    Stake(PeerId),
+    Unbond(PeerId),
+}
+
+impl Script {
+    //staking scripts are a part of transaction hash, so they are signed with the transaction
+    fn hash(&self) -> Option<String> {
+        match self {
+            Self::Stake(_) | Self::Unbond(_) => {
+                Some(HashMaker::generate(&serde_json::to_string(self).unwrap()))
+            }
+            _ => None,
+        }
+    }
 }
 
 // Define an input that includes UTXOs from other transactions' outputs, the number of UTXOs,
@@ -79,8 +118,112 @@ pub struct UnspentData {
     pub value: Decimal,
 }
 
+impl Input {
+    //input of a transaction that spends utxos
+    pub fn new(utxos: Vec<UTXO>) -> Self {
+        Self {
+            hash: HashMaker::generate(&serde_json::to_string(&utxos).unwrap()),
+            number: utxos.len() as u8,
+            utxos,
+        }
+    }
+}
+
+impl Output {
+    //output of a transaction with new unspents
+    pub fn new(unspents: Vec<Unspent>) -> Self {
+        Self {
+            hash: HashMaker::generate(&serde_json::to_string(&unspents).unwrap()),
+            number: unspents.len(),
+            unspents,
+        }
+    }
+}
+
+impl Unspent {
+    //new unspent for a wallet, salt makes hashes of equal outputs different
+    pub fn new(wallet: Public, value: Decimal) -> Self {
+        let data = UnspentData {
+            wallet,
+            salt: rand::random(),
+            value,
+        };
+        Self {
+            hash: HashMaker::generate(&serde_json::to_string(&data).unwrap()),
+            data,
+        }
+    }
+}
+
 impl Transaction {
+    //hash of a transaction is legacy merkel root of its input, output and staking script hashes
+    pub fn make_hash(input: &Input, output: &Output, script: &Script) -> String {
+        let script_hash = script.hash();
+        let mut hashes = vec![&input.hash, &output.hash];
+        if let Some(script_hash) = &script_hash {
+            hashes.push(script_hash);
+        }
+        MerkelRoot::legacy(hashes).remove(0)
+    }
+
+    #[instrument(name = "transaction", skip_all, fields(hash = %self.hash))]
     pub async fn validate<'a>(&self, db: &Database) -> Result<&Self, &'a str> {
+        self.validate_in(db, None).await
+    }
+
+    //validate transaction of a block with its number, see verify_in
+    pub async fn validate_in<'a>(&self, db: &Database, number: Option<u64>) -> Result<&Self, &'a str> {
+        let checked = self.check(db, number).await;
+        METRICS.transaction(checked.is_ok());
+        checked.map(|_| self)
+    }
+
+    async fn check<'a>(&self, db: &Database, number: Option<u64>) -> Result<(), &'a str> {
+        //check hashes, signature and values of transaction and its stake if it is unbonding
+        self.verify_in(number).map_err(|e| e.message())?;
+        Stake::unbond_validation(self, db).await.map_err(|e| e.message())?;
+
+        //validating input utxos
+        let mut is_err: Option<&str> = None;
+        for i in 0..self.input.utxos.len() {
+            match UTXO::check(&self.input.utxos[i], db, &self.signature[0].key).await {
+                Ok(_) => {}
+                Err(e) => {
+                    is_err = Some(e);
+                    break;
+                }
+            }
+        }
+
+        //if inputs utxo doesn't have any problems return true
+        match is_err {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+
+    //return utxos that transaction spends
+    pub fn inputs(&self) -> &[UTXO] {
+        &self.input.utxos
+    }
+
+    //wallet that signed the transaction
+    pub fn signer(&self) -> &Public {
+        &self.signature[0].key
+    }
+
+    //check hashes, signature and values of transaction without touching utxos
+    pub fn verify<'a>(&self) -> Result<(), &'a str> {
+        self.verify_in(None).map_err(|e| e.message())
+    }
+
+    //transactions of blocks below upgrade height can be signed over their hash only
+    //transactions that are not in a block should be signed over chain id and hash
+    pub fn verify_in<'a>(&self, number: Option<u64>) -> Result<(), TransactionError<'a>> {
+        if self.signature.is_empty() {
+            return Err(TransactionError::Invalid("Transaction is incorrect.(siganture problem!)"));
+        }
+
         //make input and output hash to check hash that is correct or not
         let inputs_str = serde_json::to_string(&self.input.utxos).unwrap();
         let outputs_str = serde_json::to_string(&self.output.unspents).unwrap();
@@ -90,47 +233,75 @@ impl Transaction {
         //check input and output hash that is correct or not
         if input_hash == self.input.hash && output_hash == self.output.hash {
             //make tansaction's hash for check that it is correct or not
-            let hashes = vec![&input_hash, &output_hash];
-            let trx_hash = MerkelRoot::make(hashes);
+            let trx_hash = Self::make_hash(&self.input, &self.output, &self.script);
 
             //check transaction hash
-            if trx_hash[0] == self.hash {
+            if trx_hash == self.hash {
                 //validating signatrue of trx
+                let signature = &self.signature[0];
+                let legacy = number.is_some_and(|number| !CHAIN_PARAMS.upgraded(number));
                 let sign_check = sp_core::ed25519::Pair::verify(
-                    &self.signature[0].signatgure,
-                    &trx_hash[0],
-                    &self.signature[0].key,
-                );
+                    &signature.signatgure,
+                    CHAIN_PARAMS.signing_payload(&trx_hash),
+                    &signature.key,
+                ) || (legacy
+                    && sp_core::ed25519::Pair::verify(&signature.signatgure, &trx_hash, &signature.key));
 
-                //if validation done transaction is correct
+                //if validation done check values, fee and staking script of transaction
                 if sign_check {
-                    //validating input utxos
-                    let mut is_err: Option<&str> = None;
-                    for i in 0..self.input.utxos.len() {
-                        match UTXO::check(&self.input.utxos[i], db, &self.signature[0].key).await {
-                            Ok(_) => {}
-                            Err(e) => {
-                                is_err = Some(e);
-                                break;
-                            }
-                        }
-                    }
-
-                    //if inputs utxo doesn't have any problems return true
-                    if is_err.is_none() {
-                        Ok(self)
-                    } else {
-                        Err(is_err.unwrap())
-                    }
+                    self.values_validation().map_err(TransactionError::Invalid)?;
+                    Stake::script_validation(self).map_err(TransactionError::Invalid)
                 } else {
-                    Err("Transaction is incorrect.(siganture problem!)")
+                    Err(TransactionError::Invalid("Transaction is incorrect.(siganture problem!)"))
                 }
             } else {
-                Err("Transaction is incorrect.(transacrtion hash problem!)")
+                Err(TransactionError::Invalid("Transaction is incorrect.(transacrtion hash problem!)"))
             }
         } else {
-            Err("Transaction is incorrect.(input/output hash problem!)")
+            Err(TransactionError::Invalid("Transaction is incorrect.(input/output hash problem!)"))
+        }
+    }
+
+    //check that inputs are equal to outputs plus fee, fee follows the fee rule of chain params
+    //and all of amounts are positive with bounded precision
+    fn values_validation<'a>(&self) -> Result<(), &'a str> {
+        if self.input.number as usize != self.input.utxos.len()
+            || self.output.number != self.output.unspents.len()
+            || self.input.utxos.is_empty()
+            || self.output.unspents.is_empty()
+        {
+            return Err("Transaction is incorrect.(input/output number problem!)");
+        }
+
+        let amounts = self
+            .input
+            .utxos
+            .iter()
+            .map(|utxo| utxo.unspent)
+            .chain(self.output.unspents.iter().map(|unspent| unspent.data.value))
+            .chain([self.value]);
+        for amount in amounts {
+            if amount <= Decimal::ZERO || amount.scale() > MAX_DECIMALS {
+                return Err("Transaction is incorrect.(amount problem!)");
+            }
+        }
+
+        if self.fee < Decimal::ZERO || self.fee != CHAIN_PARAMS.fee(self.value) {
+            return Err("Transaction is incorrect.(fee problem!)");
+        }
+
+        let inputs_sum: Decimal = self.input.utxos.iter().map(|utxo| utxo.unspent).sum();
+        let outputs_sum: Decimal = self
+            .output
+            .unspents
+            .iter()
+            .map(|unspent| unspent.data.value)
+            .sum();
+        if inputs_sum != outputs_sum + self.fee || self.value > outputs_sum {
+            return Err("Transaction is incorrect.(value problem!)");
         }
+
+        Ok(())
     }
 
     pub async fn insertion<'a>(
@@ -195,4 +366,311 @@ impl Transaction {
             Err("Querying transaction problem-(relay/practical/transaction 152)")
         }
     }
+
+    //remove a transaction from mempool of this relay and give its inputs back to its signer
+    //validators that recieved it before may still place it in a block
+    pub async fn evict<'a>(db: &'a Database, hash: &str) -> Result<Self, &'a str> {
+        let collection: Collection<Document> = db.collection("transactions");
+        let transaction: Self = match collection.find_one_and_delete(doc! {"hash": hash}).await {
+            Ok(Some(doc)) => from_document(doc).unwrap(),
+            Ok(None) => return Err("Transaction is not in mempool"),
+            Err(_) => return Err("Removing transaction from mempool problem-(relay/practical/transaction 346)"),
+        };
+        for utxo in transaction.inputs() {
+            utxo.insert(transaction.signer(), db).await?;
+        }
+        Reciept::eviction(db, &transaction.hash).await?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, time::Duration};
+
+    use mongodb::{options::ClientOptions, Client};
+    use proptest::prelude::*;
+    use sp_core::ed25519;
+
+    use super::*;
+
+    fn amount(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    // Transaction that pays value and spends inputs with the fee rule of chain params
+    fn transaction(inputs: &[&str], outputs: &[&str], value: &str) -> Transaction {
+        build(
+            inputs.iter().map(|value| amount(value)).collect(),
+            outputs.iter().map(|value| amount(value)).collect(),
+            amount(value),
+        )
+    }
+
+    fn build(inputs: Vec<Decimal>, outputs: Vec<Decimal>, value: Decimal) -> Transaction {
+        let wallet = ed25519::Pair::from_seed(&[2; 32]).public();
+        let owner = ed25519::Pair::from_seed(&[1; 32]).public();
+        Transaction {
+            hash: String::new(),
+            input: Input::new(
+                inputs
+                    .iter()
+                    .map(|value| UTXO::new(1, "trx", "output", &Unspent::new(owner, *value)))
+                    .collect(),
+            ),
+            output: Output::new(
+                outputs
+                    .iter()
+                    .map(|value| Unspent::new(wallet, *value))
+                    .collect(),
+            ),
+            value,
+            fee: CHAIN_PARAMS.fee(value),
+            script: Script::Single,
+            signature: Vec::new(),
+            date: String::new(),
+        }
+    }
+
+    // Positive amount with at most 6 decimals, so fees of values made from it keep allowed precision
+    fn amounts() -> impl Strategy<Value = Decimal> {
+        (1i64..1_000_000_000, 0u32..=6).prop_map(|(units, scale)| Decimal::new(units, scale))
+    }
+
+    // Valid transaction: value is paid by some of the outputs and inputs are the outputs
+    // grouped in chunks, with the fee added to the first one
+    fn transactions() -> impl Strategy<Value = Transaction> {
+        prop::collection::vec(amounts(), 1..8)
+            .prop_flat_map(|outputs| {
+                let len = outputs.len();
+                (Just(outputs), 1..=len, 1..=len)
+            })
+            .prop_map(|(outputs, paid, chunk)| {
+                let value: Decimal = outputs[..paid].iter().sum();
+                let mut inputs: Vec<Decimal> =
+                    outputs.chunks(chunk).map(|chunk| chunk.iter().sum()).collect();
+                inputs[0] += CHAIN_PARAMS.fee(value);
+                build(inputs, outputs, value)
+            })
+    }
+
+    #[test]
+    fn verify_errors_are_invalid() {
+        let mut trx = transaction(&["11"], &["10"], "10");
+        assert!(matches!(trx.verify_in(None), Err(TransactionError::Invalid(_))));
+        trx.signature.push(Sign {
+            signatgure: ed25519::Pair::from_seed(&[2; 32]).sign(b"other"),
+            key: ed25519::Pair::from_seed(&[2; 32]).public(),
+        });
+        assert!(matches!(trx.verify_in(None), Err(TransactionError::Invalid(_))));
+    }
+
+    // Unbond is checked against stakes in database
+    #[tokio::test]
+    async fn unbond_errors_of_database_are_query() {
+        let mut options = ClientOptions::parse("mongodb://127.0.0.1:1").await.unwrap();
+        options.server_selection_timeout = Some(Duration::from_millis(100));
+        let db = Client::with_options(options).unwrap().database("Centichain_test");
+
+        let mut trx = transaction(&["11"], &["10"], "10");
+        trx.script = Script::Unbond(PeerId::random());
+        trx.signature.push(Sign {
+            signatgure: ed25519::Pair::from_seed(&[2; 32]).sign(b""),
+            key: ed25519::Pair::from_seed(&[2; 32]).public(),
+        });
+        assert!(matches!(
+            Stake::unbond_validation(&trx, &db).await,
+            Err(TransactionError::Query(_))
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn accepts_generated_transactions(trx in transactions()) {
+            prop_assert_eq!(trx.values_validation(), Ok(()));
+        }
+
+        // Inputs must be exactly outputs and fee
+        #[test]
+        fn rejects_unconserved_values(
+            trx in transactions(),
+            delta in amounts(),
+            index in any::<prop::sample::Index>(),
+            on_input in any::<bool>(),
+        ) {
+            let mut trx = trx;
+            if on_input {
+                let i = index.index(trx.input.utxos.len());
+                trx.input.utxos[i].unspent += delta;
+            } else {
+                let i = index.index(trx.output.unspents.len());
+                trx.output.unspents[i].data.value += delta;
+            }
+            prop_assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(value problem!)")
+            );
+        }
+
+        // Value can't be more than outputs even when inputs pay its fee
+        #[test]
+        fn rejects_any_value_more_than_outputs(trx in transactions(), extra in amounts()) {
+            let mut trx = trx;
+            let outputs: Decimal = trx.output.unspents.iter().map(|unspent| unspent.data.value).sum();
+            trx.input.utxos[0].unspent += CHAIN_PARAMS.fee(outputs + extra) - trx.fee;
+            trx.value = outputs + extra;
+            trx.fee = CHAIN_PARAMS.fee(trx.value);
+            prop_assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(value problem!)")
+            );
+        }
+
+        #[test]
+        fn rejects_any_fee_out_of_rule(trx in transactions(), fee in -1_000_000i64..1_000_000, scale in 0u32..=8) {
+            let mut trx = trx;
+            let fee = Decimal::new(fee, scale);
+            prop_assume!(fee != CHAIN_PARAMS.fee(trx.value));
+            trx.fee = fee;
+            prop_assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(fee problem!)")
+            );
+        }
+
+        #[test]
+        fn rejects_wrong_numbers(trx in transactions(), inputs in any::<u8>(), outputs in 0usize..16) {
+            let mut trx = trx;
+            prop_assume!(
+                inputs as usize != trx.input.utxos.len() || outputs != trx.output.unspents.len()
+            );
+            trx.input.number = inputs;
+            trx.output.number = outputs;
+            prop_assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(input/output number problem!)")
+            );
+        }
+
+        // Any amount with more decimals than allowed is rejected even if values are conserved
+        #[test]
+        fn rejects_any_amount_with_too_many_decimals(trx in transactions(), extra in 1u32..=6, on_value in any::<bool>()) {
+            let mut trx = trx;
+            let precise = Decimal::new(1, MAX_DECIMALS + extra);
+            trx.output.unspents[0].data.value += precise;
+            trx.input.utxos[0].unspent += precise;
+            if on_value {
+                trx.value += precise;
+                trx.fee = CHAIN_PARAMS.fee(trx.value);
+            }
+            prop_assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(amount problem!)")
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_conserved_values() {
+        let fee = CHAIN_PARAMS.fee(amount("10"));
+        let input = (amount("12") + fee).to_string();
+        let trx = transaction(&[&input], &["10", "2"], "10");
+        assert_eq!(trx.values_validation(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_inputs_more_than_outputs_and_fee() {
+        let fee = CHAIN_PARAMS.fee(amount("10"));
+        let input = (amount("12") + fee).to_string();
+        let trx = transaction(&[&input, "1"], &["10", "2"], "10");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(value problem!)")
+        );
+    }
+
+    #[test]
+    fn rejects_outputs_more_than_inputs() {
+        let trx = transaction(&["10"], &["10"], "10");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(value problem!)")
+        );
+    }
+
+    #[test]
+    fn rejects_value_more_than_outputs() {
+        let fee = CHAIN_PARAMS.fee(amount("12"));
+        let input = (amount("10") + fee).to_string();
+        let trx = transaction(&[&input], &["10"], "12");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(value problem!)")
+        );
+    }
+
+    #[test]
+    fn rejects_fee_out_of_rule() {
+        let mut trx = transaction(&["11"], &["10"], "10");
+        trx.fee = amount("1");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(fee problem!)")
+        );
+        trx.fee = -CHAIN_PARAMS.fee(amount("10"));
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(fee problem!)")
+        );
+    }
+
+    #[test]
+    fn rejects_too_many_decimals() {
+        let precise = format!("1.{}1", "0".repeat(MAX_DECIMALS as usize));
+        let fee = CHAIN_PARAMS.fee(amount("10"));
+        let input = (amount("10") + amount(&precise) + fee).to_string();
+        let trx = transaction(&[&input], &["10", &precise], "10");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(amount problem!)")
+        );
+
+        let allowed = format!("1.{}1", "0".repeat(MAX_DECIMALS as usize - 1));
+        let input = (amount("10") + amount(&allowed) + fee).to_string();
+        let trx = transaction(&[&input], &["10", &allowed], "10");
+        assert_eq!(trx.values_validation(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_zero_and_negative_amounts() {
+        let fee = CHAIN_PARAMS.fee(amount("10"));
+        let input = (amount("10") + fee).to_string();
+        for trx in [
+            transaction(&[&input, "0"], &["10"], "10"),
+            transaction(&[&input], &["10", "0"], "10"),
+            transaction(&[&input, "-1"], &["10", "-1"], "10"),
+            transaction(&["1"], &["1"], "0"),
+        ] {
+            assert_eq!(
+                trx.values_validation(),
+                Err("Transaction is incorrect.(amount problem!)")
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_input_and_output_numbers() {
+        let mut trx = transaction(&["11"], &["10"], "10");
+        trx.output.number = 2;
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(input/output number problem!)")
+        );
+
+        let trx = transaction(&[], &["10"], "10");
+        assert_eq!(
+            trx.values_validation(),
+            Err("Transaction is incorrect.(input/output number problem!)")
+        );
+    }
 }
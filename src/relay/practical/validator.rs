@@ -1,5 +1,8 @@
 use libp2p::PeerId;
-use mongodb::Database;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    Collection, Database,
+};
 use serde::{Deserialize, Serialize};
 use sp_core::ed25519::Public;
 
@@ -34,4 +37,13 @@ impl Validator {
             Err(e) => Err(e),
         }
     }
+
+    //registered validator of a peerid
+    pub async fn get<'a>(db: &'a Database, peerid: &PeerId) -> Result<Option<Self>, &'a str> {
+        let collection: Collection<Document> = db.collection("validators");
+        match collection.find_one(doc! {"peerid": peerid.to_string()}).await {
+            Ok(doc) => Ok(doc.and_then(|doc| from_document(doc).ok())),
+            Err(_) => Err("Querying validator problem-(relay/practical/validator 46)"),
+        }
+    }
 }
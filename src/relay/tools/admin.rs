@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use libp2p::{Multiaddr, PeerId, Swarm};
+use mongodb::{
+    bson::{doc, Document},
+    Collection, Database,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc, oneshot, Mutex, MutexGuard},
+    time::timeout,
+};
+use tracing::{info, warn};
+
+use crate::relay::{
+    events::connections::{ConnectionsHandler, Kind},
+    practical::{
+        block::block::Block, leader::Leader, swarm::CentichainBehaviour, transaction::Transaction,
+    },
+};
+
+use super::syncer::Sync;
+
+//commands of admin api that need state of swarm loop are sent to it by this channel
+static CHANNEL: Lazy<(mpsc::Sender<AdminCommand>, Mutex<mpsc::Receiver<AdminCommand>>)> =
+    Lazy::new(|| {
+        let (sender, reciever) = mpsc::channel(16);
+        (sender, Mutex::new(reciever))
+    });
+
+//swarm loop answers between its events, so an answer can take as long as handling of an event
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub peerid: String,
+    //relay or validator, none if it didn't subscribe yet
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Vote {
+    pub voter: String,
+    pub candidate: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderInfo {
+    pub leader: Option<String>,
+    //number of the block that leader should make
+    pub round: u64,
+    //leader is replaced if its block doesn't come until this time
+    pub deadline: Option<String>,
+    pub in_check: bool,
+    pub votes: Vec<Vote>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncInfo {
+    pub synced: bool,
+    pub height: Option<u64>,
+    pub hash: Option<String>,
+}
+
+pub enum AdminCommand {
+    Connections(oneshot::Sender<Vec<ConnectionInfo>>),
+    Leader(oneshot::Sender<LeaderInfo>),
+    Sync(oneshot::Sender<SyncInfo>),
+    Dial(String, oneshot::Sender<Result<(), String>>),
+    Ban(PeerId, oneshot::Sender<Result<(), String>>),
+    Unban(PeerId, oneshot::Sender<Result<(), String>>),
+    //evicting changes utxos, so it is done between events like applying a block
+    Evict(String, oneshot::Sender<Result<String, String>>),
+}
+
+impl AdminCommand {
+    //asker stopped waiting for answer, so command must not be done anymore
+    fn is_stale(&self) -> bool {
+        match self {
+            Self::Connections(answer) => answer.is_closed(),
+            Self::Leader(answer) => answer.is_closed(),
+            Self::Sync(answer) => answer.is_closed(),
+            Self::Dial(_, answer) | Self::Ban(_, answer) | Self::Unban(_, answer) => answer.is_closed(),
+            Self::Evict(_, answer) => answer.is_closed(),
+        }
+    }
+}
+
+pub struct Admin;
+
+impl Admin {
+    //reciever of commands for swarm loop, only one loop can hold it
+    pub async fn commands() -> MutexGuard<'static, mpsc::Receiver<AdminCommand>> {
+        CHANNEL.1.lock().await
+    }
+
+    //send a command to swarm loop and wait for its answer
+    pub async fn ask<T>(command: impl FnOnce(oneshot::Sender<T>) -> AdminCommand) -> Result<T, String> {
+        let (sender, reciever) = oneshot::channel();
+        if CHANNEL.0.try_send(command(sender)).is_err() {
+            return Err("Relay is busy, try again later".to_string());
+        }
+        match timeout(ANSWER_TIMEOUT, reciever).await {
+            Ok(Ok(answer)) => Ok(answer),
+            _ => Err("Relay didn't answer, it may be syncing or restarting".to_string()),
+        }
+    }
+
+    //answer a command with state of swarm loop
+    pub async fn apply(
+        command: AdminCommand,
+        db: &Database,
+        swarm: &mut Swarm<CentichainBehaviour>,
+        connections_handler: &ConnectionsHandler,
+        leader: &Leader,
+        sync_state: &Sync,
+        last_block: &[Block],
+    ) {
+        if command.is_stale() {
+            return;
+        }
+        match command {
+            AdminCommand::Connections(answer) => {
+                let connections = connections_handler
+                    .connections
+                    .iter()
+                    .map(|conn| ConnectionInfo {
+                        peerid: conn.peerid.to_string(),
+                        kind: conn.kind.as_ref().map(|kind| match kind {
+                            Kind::Relay => "relay".to_string(),
+                            Kind::Validator => "validator".to_string(),
+                        }),
+                    })
+                    .collect();
+                answer.send(connections).ok();
+            }
+            AdminCommand::Leader(answer) => {
+                let info = LeaderInfo {
+                    leader: leader.peerid.map(|peerid| peerid.to_string()),
+                    round: last_block.first().map(|block| block.header.number + 1).unwrap_or(0),
+                    deadline: leader.time.map(|time| time.to_rfc3339()),
+                    in_check: leader.in_check,
+                    votes: leader
+                        .votes
+                        .iter()
+                        .map(|(voter, candidate)| Vote {
+                            voter: voter.to_string(),
+                            candidate: candidate.to_string(),
+                        })
+                        .collect(),
+                };
+                answer.send(info).ok();
+            }
+            AdminCommand::Sync(answer) => {
+                let info = SyncInfo {
+                    synced: *sync_state == Sync::Synced,
+                    height: last_block.first().map(|block| block.header.number),
+                    hash: last_block.first().map(|block| block.header.hash.clone()),
+                };
+                answer.send(info).ok();
+            }
+            AdminCommand::Dial(addr, answer) => {
+                let dialed = match addr.parse::<Multiaddr>() {
+                    Ok(multiaddr) => match swarm.dial(multiaddr) {
+                        Ok(_) => {
+                            info!("Dialing with {} by admin", addr);
+                            Ok(())
+                        }
+                        Err(e) => Err(format!("Dialing {} problem: {}", addr, e)),
+                    },
+                    Err(_) => Err(format!("Address {} is not a multiaddr", addr)),
+                };
+                answer.send(dialed).ok();
+            }
+            AdminCommand::Ban(peerid, answer) => {
+                let banned = match Bans::insert(db, &peerid).await {
+                    Ok(_) => {
+                        //blocking closes connections of peer too
+                        swarm.behaviour_mut().blocked.block_peer(peerid);
+                        warn!("Peer {} banned by admin", peerid);
+                        Ok(())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                answer.send(banned).ok();
+            }
+            AdminCommand::Unban(peerid, answer) => {
+                let unbanned = match Bans::remove(db, &peerid).await {
+                    Ok(_) => {
+                        swarm.behaviour_mut().blocked.unblock_peer(peerid);
+                        info!("Peer {} unbanned by admin", peerid);
+                        Ok(())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                answer.send(unbanned).ok();
+            }
+            AdminCommand::Evict(hash, answer) => {
+                let evicted = match Transaction::evict(db, &hash).await {
+                    Ok(transaction) => {
+                        warn!("Transaction {} evicted from mempool by admin", transaction.hash);
+                        Ok(transaction.hash)
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                answer.send(evicted).ok();
+            }
+        }
+    }
+}
+
+//banned peers are kept in database, so they stay banned after restarts
+pub struct Bans;
+
+impl Bans {
+    pub async fn get(db: &Database) -> Result<Vec<PeerId>, &str> {
+        let collection: Collection<Document> = db.collection("banned_peers");
+        match collection.distinct("peerid", doc! {}).await {
+            Ok(peerids) => Ok(peerids
+                .iter()
+                .filter_map(|peerid| peerid.as_str())
+                .filter_map(|peerid| peerid.parse().ok())
+                .collect()),
+            Err(_) => Err("Querying banned peers problem-(relay/tools/admin 210)"),
+        }
+    }
+
+    //block banned peers in a new swarm
+    pub async fn apply<'a>(db: &'a Database, swarm: &mut Swarm<CentichainBehaviour>) -> Result<(), &'a str> {
+        for peerid in Self::get(db).await? {
+            swarm.behaviour_mut().blocked.block_peer(peerid);
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(db: &'a Database, peerid: &PeerId) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("banned_peers");
+        let filter = doc! {"peerid": peerid.to_string()};
+        let update = doc! {"$set": {"peerid": peerid.to_string()}};
+        match collection.update_one(filter, update).upsert(true).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Saving banned peer problem-(relay/tools/admin 228)"),
+        }
+    }
+
+    async fn remove<'a>(db: &'a Database, peerid: &PeerId) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("banned_peers");
+        match collection.delete_one(doc! {"peerid": peerid.to_string()}).await {
+            Ok(result) if result.deleted_count > 0 => Ok(()),
+            Ok(_) => Err("Peer is not banned"),
+            Err(_) => Err("Removing banned peer problem-(relay/tools/admin 237)"),
+        }
+    }
+}
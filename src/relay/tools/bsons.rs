@@ -1,8 +1,9 @@
 use std::{fs::File, io::BufReader};
 
 use mongodb::{bson::Document, Collection, Database};
+use tracing::info;
 
-use super::create_log::write_log;
+use crate::relay::practical::config::CONFIG;
 
 pub struct Bson;
 
@@ -13,7 +14,7 @@ impl Bson {
         bson: &str,
     ) -> Result<(), &'a str> {
         // Construct the full path to the BSON file
-        let bson_addr = format!("./etc/dump/Centichain/{}", bson);
+        let bson_addr = format!("{}/{}", CONFIG.sync_dir(), bson);
         let open_file = File::open(bson_addr.clone());
         match open_file {
             Ok(file) => {
@@ -28,7 +29,8 @@ impl Bson {
                     collection.insert_one(doc).await.unwrap();
                 }
                 // Log successful synchronization
-                Ok(write_log(&format!("{} Synced", collection_name)))
+                info!("{} Synced", collection_name);
+                Ok(())
             }
             Err(_e) => Err("Your file address is incorrect!-(tools/bsons 31)"),
         }
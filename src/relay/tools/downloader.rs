@@ -1,8 +1,9 @@
 use std::{fs::File, io::Write};
 
 use libp2p::futures::StreamExt;
+use tracing::{debug, info};
 
-use super::create_log::write_log;
+use super::metrics::METRICS;
 
 pub struct Downloader;
 
@@ -23,15 +24,17 @@ impl Downloader {
                             output.write_all(&chunk).unwrap();
                             i += chunk.len() as f64;
                             let percent = i / (file_size as f64) * 100.0;
+                            METRICS.sync_download(percent);
                             if percent.round() == 100.0 {
-                                write_log(&format!("Blockchain downloading: {}", percent.round()))
+                                debug!("Blockchain downloading: {}", percent.round())
                             }
                         }
                         None => break,
                     }
                 }
                 output.flush().unwrap();
-                Ok(write_log("Blockchain downloaded."))
+                info!("Blockchain downloaded.");
+                Ok(())
             }
             Err(_e) => Err("Request Problem For Download-(tools/downloader-35)"),
         }
@@ -0,0 +1,203 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    sync::Mutex,
+};
+
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, EnvFilter};
+
+use crate::relay::practical::config::{LogConfig, CONFIG};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rotation {
+    Daily,
+    Hourly,
+    Never,
+}
+
+impl Rotation {
+    fn parse(rotation: &str) -> Result<Self, String> {
+        match rotation {
+            "daily" => Ok(Self::Daily),
+            "hourly" => Ok(Self::Hourly),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("Log rotation {} is not daily, hourly or never", rotation)),
+        }
+    }
+
+    //period that a log file belongs to, file is rotated when it changes
+    fn period(&self) -> String {
+        match self {
+            Self::Daily => Utc::now().format("%Y%m%d").to_string(),
+            Self::Hourly => Utc::now().format("%Y%m%d%H").to_string(),
+            Self::Never => String::new(),
+        }
+    }
+}
+
+//log file that is rotated when it gets bigger than max size or its period ends
+//rotated files are renamed to <path>.1 (newest) to <path>.<max files>
+pub struct RotatingFile {
+    path: String,
+    file: File,
+    size: u64,
+    period: String,
+    max_size: u64,
+    rotation: Rotation,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(config: &LogConfig) -> Result<Self, String> {
+        let rotation = Rotation::parse(&config.rotation)?;
+        let file = Self::append(&config.path)?;
+        let metadata = file.metadata().map_err(|e| e.to_string())?;
+        //period of an existing file is taken from its last change, so old logs are rotated at start
+        let period = match metadata.modified() {
+            Ok(modified) if rotation != Rotation::Never => {
+                let modified: chrono::DateTime<Utc> = modified.into();
+                match rotation {
+                    Rotation::Daily => modified.format("%Y%m%d").to_string(),
+                    _ => modified.format("%Y%m%d%H").to_string(),
+                }
+            }
+            _ => rotation.period(),
+        };
+        Ok(Self {
+            path: config.path.clone(),
+            file,
+            size: metadata.len(),
+            period,
+            max_size: config.max_size.saturating_mul(1024 * 1024),
+            rotation,
+            max_files: config.max_files,
+        })
+    }
+
+    fn append(path: &str) -> Result<File, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Opening log file {} problem: {}", path, e))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            fs::remove_file(format!("{}.{}", self.path, self.max_files)).ok();
+            for i in (1..self.max_files).rev() {
+                fs::rename(
+                    format!("{}.{}", self.path, i),
+                    format!("{}.{}", self.path, i + 1),
+                )
+                .ok();
+            }
+            fs::rename(&self.path, format!("{}.1", self.path))?;
+            self.file = Self::append(&self.path).map_err(io::Error::other)?;
+        }
+        self.size = 0;
+        self.period = self.rotation.period();
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let full = self.max_size > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_size;
+        if full || self.rotation.period() != self.period {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+//changes filter of installed subscriber, it is set by Logger::init
+type Reloader = Box<dyn Fn(EnvFilter) -> Result<(), String> + Send + Sync>;
+static RELOADER: OnceCell<Reloader> = OnceCell::new();
+static FILTER: Mutex<String> = Mutex::new(String::new());
+
+pub struct Logger;
+
+impl Logger {
+    //install tracing subscriber by CONFIG.log
+    //if log file can't be opened logs are written to stderr and the problem is returned
+    pub fn init() -> Result<(), String> {
+        let config = &CONFIG.log;
+        let (filter, filter_err) = match EnvFilter::try_new(&config.filter) {
+            Ok(filter) => (filter, None),
+            Err(e) => (
+                EnvFilter::new("info"),
+                Some(format!("Log filter {} is incorrect: {}", config.filter, e)),
+            ),
+        };
+        let (writer, file_err) = match config.path.as_str() {
+            "stdout" => (BoxMakeWriter::new(io::stdout), None),
+            _ => match RotatingFile::open(config) {
+                Ok(file) => (BoxMakeWriter::new(Mutex::new(file)), None),
+                Err(e) => (BoxMakeWriter::new(io::stderr), Some(e)),
+            },
+        };
+
+        *FILTER.lock().unwrap() = filter.to_string();
+
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(writer)
+            .with_ansi(false);
+        let installed = if config.json {
+            let builder = builder
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            RELOADER
+                .set(Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())))
+                .ok();
+            builder.try_init()
+        } else {
+            let builder = builder.with_filter_reloading();
+            let handle = builder.reload_handle();
+            RELOADER
+                .set(Box::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())))
+                .ok();
+            builder.try_init()
+        };
+        installed.map_err(|e| e.to_string())?;
+
+        match filter_err.or(file_err) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    //filter of logs that is used now
+    pub fn filter() -> String {
+        FILTER.lock().unwrap().clone()
+    }
+
+    //change filter of logs while relay is running, like `debug` or `info,relay_node::relay::events=trace`
+    pub fn set_filter(filter: &str) -> Result<(), String> {
+        let new_filter = EnvFilter::try_new(filter)
+            .map_err(|e| format!("Log filter {} is incorrect: {}", filter, e))?;
+        match RELOADER.get() {
+            Some(reload) => {
+                reload(new_filter)?;
+                *FILTER.lock().unwrap() = filter.to_string();
+                Ok(())
+            }
+            None => Err("Logger is not initialized".to_string()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+//prefixes of leaf and node hashes, so a leaf can't be presented as a node of tree
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+pub struct MerkelRoot;
+
+//side of a sibling hash in a proof step
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+//sibling hashes from a leaf up to the root
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MerkelProof {
+    pub leaf: String,
+    pub index: usize,
+    pub leaves: usize,
+    pub steps: Vec<(Side, String)>,
+}
+
+impl MerkelRoot {
+    fn leaf(hash: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(hash);
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn node(left: &str, right: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update([NODE_PREFIX]);
+        hasher.update(left);
+        hasher.update(right);
+        format!("{:x}", hasher.finalize())
+    }
+
+    //next level of tree, last node of an odd level goes up without hashing
+    fn level(nodes: &[String]) -> Vec<String> {
+        nodes
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => Self::node(left, right),
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    //make a merkel root from hashs of transactions
+    //returns an empty vector if there is no any hash
+    pub fn make(transactions: Vec<&String>) -> Vec<String> {
+        let mut nodes: Vec<String> = transactions.iter().map(|hash| Self::leaf(hash)).collect();
+        while nodes.len() > 1 {
+            nodes = Self::level(&nodes);
+        }
+        nodes
+    }
+
+    //merkel root of blocks below upgrade height and hashes of transactions
+    //two hashes from the front of queue are hashed together and pushed to its back
+    pub fn legacy(transactions: Vec<&String>) -> Vec<String> {
+        let mut hashs: Vec<String> = transactions.iter().map(|hash| hash.to_string()).collect();
+        while hashs.len() > 1 {
+            let left = hashs.remove(0);
+            let right = hashs.remove(0);
+            let mut hasher = Sha256::new();
+            hasher.update(left);
+            hasher.update(right);
+            hashs.push(format!("{:x}", hasher.finalize()));
+        }
+        hashs
+    }
+
+    //make inclusion proof of a hash in hashs of transactions
+    pub fn proof(transactions: Vec<&String>, hash: &str) -> Option<MerkelProof> {
+        let index = transactions.iter().position(|trx| *trx == hash)?;
+        let mut nodes: Vec<String> = transactions.iter().map(|hash| Self::leaf(hash)).collect();
+        let mut position = index;
+        let mut steps = Vec::new();
+        while nodes.len() > 1 {
+            let sibling = position ^ 1;
+            if sibling < nodes.len() {
+                let side = if sibling < position { Side::Left } else { Side::Right };
+                steps.push((side, nodes[sibling].clone()));
+            }
+            nodes = Self::level(&nodes);
+            position /= 2;
+        }
+        Some(MerkelProof {
+            leaf: hash.to_string(),
+            index,
+            leaves: transactions.len(),
+            steps,
+        })
+    }
+}
+
+impl MerkelProof {
+    //check that leaf of proof is in a tree with the root
+    //positions of siblings should match index of leaf, so a proof can't be moved to another index
+    pub fn verify(&self, root: &str) -> bool {
+        if self.index >= self.leaves {
+            return false;
+        }
+        let mut hash = MerkelRoot::leaf(&self.leaf);
+        let mut steps = self.steps.iter();
+        let mut position = self.index;
+        let mut width = self.leaves;
+        while width > 1 {
+            let sibling = position ^ 1;
+            if sibling < width {
+                match steps.next() {
+                    Some((Side::Left, node)) if sibling < position => {
+                        hash = MerkelRoot::node(node, &hash)
+                    }
+                    Some((Side::Right, node)) if sibling > position => {
+                        hash = MerkelRoot::node(&hash, node)
+                    }
+                    _ => return false,
+                }
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        steps.next().is_none() && hash == root
+    }
+}
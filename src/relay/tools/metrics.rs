@@ -0,0 +1,200 @@
+use chrono::Utc;
+use mongodb::{
+    bson::{doc, Document},
+    Collection, Database,
+};
+use once_cell::sync::Lazy;
+use prometheus_client::{
+    encoding::{text::encode, EncodeLabelSet},
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{exponential_buckets, Histogram},
+    },
+    registry::Registry,
+};
+
+use crate::relay::events::connections::{ConnectionsHandler, Kind};
+
+use super::get_last_block::LastBlock;
+
+//metrics of relay that are served on /metrics in prometheus text format
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct BlockLabels {
+    pub result: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ResultLabels {
+    pub result: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct TopicLabels {
+    pub topic: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RouteLabels {
+    pub route: String,
+}
+
+pub struct Metrics {
+    registry: Registry,
+    relays: Gauge,
+    validators: Gauge,
+    height: Gauge,
+    last_block_age: Gauge,
+    mempool: Gauge,
+    blocks: Family<BlockLabels, Counter>,
+    transactions: Family<ResultLabels, Counter>,
+    leader_changes: Counter,
+    leader_votes: Counter,
+    gossip_failures: Family<TopicLabels, Counter>,
+    synced: Gauge,
+    sync_download: Gauge,
+    rpc_latency: Family<RouteLabels, Histogram, fn() -> Histogram>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut metrics = Self {
+            registry: Registry::with_prefix("centichain"),
+            relays: Gauge::default(),
+            validators: Gauge::default(),
+            height: Gauge::default(),
+            last_block_age: Gauge::default(),
+            mempool: Gauge::default(),
+            blocks: Family::default(),
+            transactions: Family::default(),
+            leader_changes: Counter::default(),
+            leader_votes: Counter::default(),
+            gossip_failures: Family::default(),
+            synced: Gauge::default(),
+            sync_download: Gauge::default(),
+            //from 1ms to about 16s
+            rpc_latency: Family::new_with_constructor(|| {
+                Histogram::new(exponential_buckets(0.001, 2.0, 15))
+            }),
+        };
+        let registry = &mut metrics.registry;
+        registry.register("connected_relays", "Relays connected to this relay", metrics.relays.clone());
+        registry.register("connected_validators", "Validators connected to this relay", metrics.validators.clone());
+        registry.register("chain_height", "Number of the last block", metrics.height.clone());
+        registry.register("last_block_age_seconds", "Seconds since date of the last block", metrics.last_block_age.clone());
+        registry.register("mempool_transactions", "Transactions waiting to be placed in a block", metrics.mempool.clone());
+        registry.register("blocks", "Received blocks by result and rejection reason", metrics.blocks.clone());
+        registry.register("transactions", "Validated transactions by result", metrics.transactions.clone());
+        registry.register("leader_changes", "Changes of leader", metrics.leader_changes.clone());
+        registry.register("leader_votes", "Votes for a new leader received from validators", metrics.leader_votes.clone());
+        registry.register("gossip_publish_failures", "Failed gossip publishes by topic", metrics.gossip_failures.clone());
+        registry.register("synced", "1 if relay is synced with the network", metrics.synced.clone());
+        registry.register("sync_download_percent", "Downloaded percent of blockchain while syncing", metrics.sync_download.clone());
+        registry.register("rpc_latency_seconds", "Latency of RPC requests by route", metrics.rpc_latency.clone());
+        metrics
+    }
+
+    pub fn connections(&self, connections_handler: &ConnectionsHandler) {
+        let count = |kind: Kind| {
+            connections_handler
+                .connections
+                .iter()
+                .filter(|conn| conn.kind.as_ref() == Some(&kind))
+                .count() as i64
+        };
+        self.relays.set(count(Kind::Relay));
+        self.validators.set(count(Kind::Validator));
+    }
+
+    pub fn block_accepted(&self) {
+        self.block("accepted", "");
+    }
+
+    //reason is an error of relay, its location part is removed so that same errors are counted together
+    pub fn block_rejected(&self, reason: &str) {
+        let reason = reason.split("-(").next().unwrap_or(reason).trim();
+        self.block("rejected", reason);
+    }
+
+    fn block(&self, result: &str, reason: &str) {
+        self.blocks
+            .get_or_create(&BlockLabels {
+                result: result.to_string(),
+                reason: reason.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn transaction(&self, valid: bool) {
+        let result = if valid { "validated" } else { "rejected" };
+        self.transactions
+            .get_or_create(&ResultLabels {
+                result: result.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn leader_changed(&self) {
+        self.leader_changes.inc();
+    }
+
+    pub fn leader_voted(&self) {
+        self.leader_votes.inc();
+    }
+
+    pub fn gossip_failed(&self, topic: &str) {
+        self.gossip_failures
+            .get_or_create(&TopicLabels {
+                topic: topic.to_string(),
+            })
+            .inc();
+    }
+
+    pub fn synced(&self, synced: bool) {
+        self.synced.set(synced as i64);
+    }
+
+    pub fn sync_download(&self, percent: f64) {
+        self.sync_download.set(percent.round() as i64);
+    }
+
+    pub fn rpc_latency(&self, route: &str, seconds: f64) {
+        self.rpc_latency
+            .get_or_create(&RouteLabels {
+                route: route.to_string(),
+            })
+            .observe(seconds);
+    }
+
+    //update metrics that are read from database and encode all of them
+    pub async fn render<'a>(&self, db: &'a Database) -> Result<String, &'a str> {
+        if let Some(block) = LastBlock::get(db).await? {
+            self.height.set(block.header.number as i64);
+            if let Some(timestamp) = block.header.timestamp() {
+                self.last_block_age.set(Utc::now().timestamp() - timestamp);
+            }
+        }
+        let collection: Collection<Document> = db.collection("transactions");
+        match collection.count_documents(doc! {}).await {
+            Ok(count) => {
+                self.mempool.set(count as i64);
+            }
+            Err(_) => return Err("Counting mempool transactions problem-(relay/tools/metrics 186)"),
+        }
+
+        self.encode()
+    }
+
+    //encode all of metrics in prometheus text format
+    pub fn encode<'a>(&self) -> Result<String, &'a str> {
+        let mut body = String::new();
+        match encode(&mut body, &self.registry) {
+            Ok(_) => Ok(body),
+            Err(_) => Err("Encoding metrics problem-(relay/tools/metrics 197)"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use once_cell::sync::Lazy;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+//token that is shared by swarm loop, rpc server and middlegossiper
+//cancelling it stops all of them after their in-flight work is finished
+static TOKEN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+pub struct Shutdown;
+
+impl Shutdown {
+    pub fn token() -> CancellationToken {
+        TOKEN.clone()
+    }
+
+    pub fn is_requested() -> bool {
+        TOKEN.is_cancelled()
+    }
+
+    //stop the node normally(signals)
+    pub fn request(reason: &str) {
+        if !TOKEN.is_cancelled() {
+            info!("Shutdown requested: {}", reason);
+            TOKEN.cancel();
+        }
+    }
+
+    //stop the node because of an error that it can not continue with
+    //process exits with non-zero code after everything stopped
+    pub fn fatal(error: &str) {
+        error!("Fatal error, shutting down: {}", error);
+        EXIT_CODE.store(1, Ordering::SeqCst);
+        TOKEN.cancel();
+    }
+
+    pub fn exit_code() -> i32 {
+        EXIT_CODE.load(Ordering::SeqCst)
+    }
+
+    //wait for SIGINT or SIGTERM and then request shutdown
+    //it returns when shutdown was requested from another place too
+    pub async fn signals() {
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(_) => std::future::pending::<()>().await,
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => Self::request("SIGINT"),
+            _ = terminate => Self::request("SIGTERM"),
+            _ = TOKEN.cancelled() => {}
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::{task::JoinHandle, time::sleep};
+use tracing::warn;
+
+use super::shutdown::Shutdown;
+
+//a task that ran this long without failing is stable again and its restarts are counted from zero
+const STABLE_AFTER: Duration = Duration::from_secs(300);
+
+//health of supervised tasks by their names
+static HEALTH: Lazy<Mutex<BTreeMap<String, TaskHealth>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Restart {
+    //task is restarted when it returns an error or panics
+    OnFailure,
+    //task is restarted whenever it ends
+    Always,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    pub restart: Restart,
+    //restarts allowed before the node stops, None means without limit
+    pub max_restarts: Option<u32>,
+    //first delay before restarting that doubles after each restart
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Policy {
+    pub fn new(restart: Restart) -> Self {
+        Self {
+            restart,
+            max_restarts: None,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    pub fn max_restarts(mut self, max_restarts: u32) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Duration, max_backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    //delay before a restart with the given number
+    fn delay(&self, restart: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(restart.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Running,
+    Restarting,
+    Stopped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskHealth {
+    pub name: String,
+    pub status: Status,
+    pub restarts: u32,
+    //why the task died the last time
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    runners: Vec<JoinHandle<()>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self {
+            runners: Vec::new(),
+        }
+    }
+
+    //spawn a named task that is restarted by its policy
+    //task makes a new future for each run, errors and panics of a run are reported as its death reason
+    pub fn spawn<F, Fut>(&mut self, name: &str, policy: Policy, task: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let name = name.to_string();
+        let runner = tokio::spawn(async move {
+            let shutdown = Shutdown::token();
+            let mut restarts = 0;
+            loop {
+                Self::report(&name, Status::Running, restarts, None);
+                let started = Instant::now();
+                let failure = match tokio::spawn(task()).await {
+                    Ok(Ok(())) => None,
+                    Ok(Err(e)) => Some(e),
+                    Err(e) if e.is_panic() => Some(format!("panicked: {}", panic_message(e.into_panic()))),
+                    Err(_) => Some("cancelled".to_string()),
+                };
+
+                if Shutdown::is_requested() {
+                    Self::report(&name, Status::Stopped, restarts, failure);
+                    break;
+                }
+                if let Some(e) = &failure {
+                    warn!("Task {} died: {}", name, e);
+                }
+
+                let restart = match policy.restart {
+                    Restart::OnFailure => failure.is_some(),
+                    Restart::Always => true,
+                };
+                if !restart {
+                    match failure {
+                        Some(e) => {
+                            Self::report(&name, Status::Failed, restarts, Some(e.clone()));
+                            Shutdown::fatal(&format!("Task {} failed: {}", name, e));
+                        }
+                        None => Self::report(&name, Status::Stopped, restarts, None),
+                    }
+                    break;
+                }
+
+                if started.elapsed() >= STABLE_AFTER {
+                    restarts = 0;
+                }
+                restarts += 1;
+                if policy.max_restarts.is_some_and(|max| restarts > max) {
+                    let reason = failure.unwrap_or("it stopped".to_string());
+                    Self::report(&name, Status::Failed, restarts - 1, Some(reason.clone()));
+                    Shutdown::fatal(&format!(
+                        "Task {} failed after {} restarts: {}",
+                        name,
+                        restarts - 1,
+                        reason
+                    ));
+                    break;
+                }
+
+                let delay = policy.delay(restarts);
+                Self::report(&name, Status::Restarting, restarts, failure);
+                warn!("Restarting task {} in {:?}", name, delay);
+                tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        Self::report(&name, Status::Stopped, restarts, None);
+                        break;
+                    }
+                    _ = sleep(delay) => {}
+                }
+            }
+        });
+        self.runners.push(runner);
+    }
+
+    //wait until all tasks stopped, node shuts down if none of them is running anymore
+    pub async fn wait(self) {
+        for runner in self.runners {
+            runner.await.ok();
+        }
+        Shutdown::request("all tasks stopped");
+    }
+
+    pub fn health() -> Vec<TaskHealth> {
+        HEALTH.lock().unwrap().values().cloned().collect()
+    }
+
+    fn report(name: &str, status: Status, restarts: u32, error: Option<String>) {
+        let mut health = HEALTH.lock().unwrap();
+        let task = health.entry(name.to_string()).or_insert(TaskHealth {
+            name: name.to_string(),
+            status: status.clone(),
+            restarts,
+            last_error: None,
+        });
+        task.status = status;
+        task.restarts = restarts;
+        if error.is_some() {
+            task.last_error = error;
+        }
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
@@ -7,27 +7,127 @@ use mongodb::{
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
-use sp_core::ed25519::Public;
+use sp_core::{ed25519::Public, Pair};
+use tracing::{info, warn};
 
 use crate::relay::practical::{
-    block::{block::Block, message::BlockMessage},
+    block::{block::Block, header::Sign, message::BlockMessage},
+    chain_params::CHAIN_PARAMS,
+    config::CONFIG,
+    genesis::GenesisSpec,
     leader::Leader,
     relay::{DialedRelays, RelayStruct},
+    stake::Stake,
     validator::Validator,
 };
 
-use super::{bsons::Bson, create_log::write_log, downloader::Downloader, zipp::Zip};
+use super::{bsons::Bson, downloader::Downloader, metrics::METRICS, zipp::Zip};
 
+//message that a synced validator gossips for registering itself
+//sign is signature of wallet over peerid, relay, chain id and nonce of registration
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VSync {
     relay: PeerId,
     peerid: PeerId,
     msg: String,
     wallet: Public,
+    nonce: u64,
+    sign: Sign,
 }
 
 impl VSync {
-    pub async fn handle<'a>(&self, db: &'a Database, leader: &mut Leader) -> Result<(), &'a str> {
+    //payload that wallet of validator signs for registration
+    pub fn payload(peerid: &PeerId, relay: &PeerId, nonce: u64) -> String {
+        CHAIN_PARAMS.signing_payload(&format!("vsync:{}:{}:{}", peerid, relay, nonce))
+    }
+
+    //check that validator owns the wallet, message is from the validator itself
+    //and relay of validator is the relay that propagated the message (or this relay if validator is directly connected)
+    fn check(
+        &self,
+        source: Option<PeerId>,
+        propagation_source: PeerId,
+        local_peerid: PeerId,
+    ) -> Result<(), &'static str> {
+        if source != Some(self.peerid) {
+            return Err("Validator registration is not from the validator itself.");
+        }
+        let relay_check = if propagation_source == self.peerid {
+            self.relay == local_peerid
+        } else {
+            self.relay == propagation_source
+        };
+        if !relay_check {
+            return Err("Validator registration has a wrong relay.");
+        }
+        if self.sign.key != self.wallet
+            || !sp_core::ed25519::Pair::verify(
+                &self.sign.signatgure,
+                Self::payload(&self.peerid, &self.relay, self.nonce),
+                &self.sign.key,
+            )
+        {
+            return Err("Validator registration signature is incorrect.");
+        }
+        Ok(())
+    }
+
+    //nonce of registration should be greater than the last nonce of validator to prevent replaying
+    async fn nonce_check<'a>(&self, db: &'a Database) -> Result<bool, &'a str> {
+        let collection: Collection<Document> = db.collection("registrations");
+        let filter = doc! {"peerid": self.peerid.to_string()};
+        match collection.find_one(filter.clone()).await {
+            Ok(Some(doc)) if doc.get_i64("nonce").unwrap_or(0) >= self.nonce as i64 => Ok(false),
+            Ok(_) => {
+                let update = doc! {"$set": {"nonce": self.nonce as i64}};
+                match collection.update_one(filter, update).upsert(true).await {
+                    Ok(_) => Ok(true),
+                    Err(_) => Err("Updating registration nonce problem-(relay/tools/syncer 83)"),
+                }
+            }
+            Err(_) => Err("Querying registration nonce problem-(relay/tools/syncer 86)"),
+        }
+    }
+
+    //validator should have minimum stake for entering validators
+    //except the first validator of network that has no way to stake before it
+    async fn bond_check<'a>(&self, db: &'a Database) -> Result<bool, &'a str> {
+        let collection: Collection<Document> = db.collection("validators");
+        match collection.count_documents(doc! {}).await {
+            Ok(0) => Ok(true),
+            Ok(_) => Ok(Stake::bonded(db, &self.peerid).await? >= CHAIN_PARAMS.min_stake),
+            Err(_) => Err("Get count of validators problem-(relay/tools/syncer 98)"),
+        }
+    }
+
+    pub async fn handle<'a>(
+        &self,
+        db: &'a Database,
+        leader: &mut Leader,
+        source: Option<PeerId>,
+        propagation_source: PeerId,
+        local_peerid: PeerId,
+    ) -> Result<(), &'a str> {
+        //wrong registrations are ignored
+        if let Err(e) = self.check(source, propagation_source, local_peerid) {
+            warn!("Registration of {} rejected: {}", self.peerid, e);
+            return Ok(());
+        }
+        if !self.bond_check(db).await? {
+            warn!(
+                "Registration of {} rejected: bonded stake is less than minimum stake.",
+                self.peerid
+            );
+            return Ok(());
+        }
+        if !self.nonce_check(db).await? {
+            warn!(
+                "Registration of {} rejected: nonce is used before.",
+                self.peerid
+            );
+            return Ok(());
+        }
+
         match Validator::new(db, self.peerid, self.relay, self.wallet, leader).await {
             Ok(validator) => {
                 let collection: Collection<Document> = db.collection("validators");
@@ -42,10 +142,10 @@ impl VSync {
                         .insert_one(to_document(&validator).unwrap())
                         .await
                     {
-                        Ok(_) => Ok(write_log(&format!(
-                            "New synced validator added: {}",
-                            self.peerid
-                        ))),
+                        Ok(_) => {
+                            info!("New synced validator added: {}", self.peerid);
+                            Ok(())
+                        }
                         Err(_) => {
                             Err("Error while inserting new validator-(relay/tools/syncer 48)")
                         }
@@ -68,8 +168,8 @@ impl Syncer {
             Ok(splited_addr) => {
                 //start downloading blockchain from connected relay
                 let url = format!("http://{}:33369/blockchain/Centichain.zip", splited_addr);
-                match Downloader::download(&url, "/home/Centichain.zip").await {
-                    Ok(_) => match Zip::extract("./etc/dump/Centichain") {
+                match Downloader::download(&url, &CONFIG.zip_path()).await {
+                    Ok(_) => match Zip::extract(&CONFIG.sync_dir()) {
                         Ok(_) => Ok(()),
                         Err(e) => Err(e),
                     },
@@ -92,7 +192,7 @@ impl Syncer {
             Ok(_) => {
                 //add bsons to database or mempool by a loop
                 //add bsons to database or mempool by a loop
-                let path = "./etc/dump/Centichain";
+                let path = CONFIG.sync_dir();
                 for entry in fs::read_dir(path).unwrap() {
                     let item = entry.unwrap();
                     let file_name = item.file_name();
@@ -113,7 +213,8 @@ impl Syncer {
                 if error.is_some() {
                     Err(error.unwrap())
                 } else {
-                    Ok(write_log("Blockchain inserted to mongodb successfully."))
+                    info!("Blockchain inserted to mongodb successfully.");
+                    Ok(())
                 }
             }
             Err(e) => Err(e),
@@ -130,6 +231,9 @@ impl Syncer {
     ) -> Result<(), &'a str> {
         match Self::insert_bsons(db, dialed_relays).await {
             Ok(_) => {
+                //synced chain should have the same genesis as this relay
+                GenesisSpec::check(db).await?;
+
                 let mut is_err = None;
                 //finding last block after inserted bsons
                 let collection: Collection<Document> = db.collection("Blocks");
@@ -152,7 +256,7 @@ impl Syncer {
                             leader.update(Some(msg.next_leader));
                         }
                         Err(e) => {
-                            is_err.get_or_insert(e);
+                            is_err.get_or_insert(e.message());
                         }
                     }
                 }
@@ -168,9 +272,10 @@ impl Syncer {
 }
 
 //define sync for checks that relay is sync or not
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Default)]
 pub enum Sync {
     Synced,
+    #[default]
     NotSynced,
 }
 
@@ -180,7 +285,8 @@ impl Sync {
     }
 
     pub fn synced(&mut self) {
-        write_log("Relay Syncing Completed. :)");
+        info!("Relay Syncing Completed. :)");
+        METRICS.synced(true);
         *self = Self::Synced;
     }
 }
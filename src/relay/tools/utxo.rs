@@ -35,6 +35,17 @@ pub struct UTXO {
 }
 
 impl UTXO {
+    //utxo of an unspent output of a transaction in a block
+    pub fn new(block: u64, trx_hash: &str, output_hash: &str, unspent: &Unspent) -> Self {
+        Self {
+            block,
+            trx_hash: trx_hash.to_string(),
+            output_hash: output_hash.to_string(),
+            unspent_hash: unspent.hash.to_string(),
+            unspent: unspent.data.value,
+        }
+    }
+
     //check a utxo that if exist remove it, else return error
     pub async fn check<'a>(&self, db: &Database, wallet: &Public) -> Result<(), &'a str> {
         //query from UTXOs collection for find person's utxos
@@ -49,7 +60,7 @@ impl UTXO {
                     let index = person
                         .utxos
                         .iter()
-                        .position(|u| u.unspent_hash == self.unspent_hash); //find index of utxo in the utxos variant of person structure that is a vector of utxo
+                        .position(|u| u.unspent_hash == self.unspent_hash && u.unspent == self.unspent); //find index of utxo in the utxos variant of person structure that is a vector of utxo
 
                     //if there is an index remove it from person's utxos and update documnet of collection
                     //else return error
@@ -77,23 +88,52 @@ impl UTXO {
         }
     }
 
+    //check that a utxo exists without removing it
+    pub async fn exists<'a>(&self, db: &Database, wallet: &Public) -> Result<bool, &'a str> {
+        let collection: Collection<Document> = db.collection("UTXOs");
+        let filter = doc! {"wallet": wallet.to_string()};
+        match collection.find_one(filter).await {
+            Ok(Some(doc)) => {
+                let person: Person = from_document(doc).unwrap();
+                Ok(person
+                    .utxos
+                    .iter()
+                    .any(|u| u.unspent_hash == self.unspent_hash && u.unspent == self.unspent))
+            }
+            Ok(None) => Ok(false),
+            Err(_) => Err("Problem from query of utxos collection-(tools/utxo 80)"),
+        }
+    }
+
+    //remove a utxo that a transaction of a block spends, it is nothing if the utxo is removed before
+    //like inputs of a transaction that were spent at its admission
+    pub async fn spend<'a>(&self, db: &Database, wallet: &Public) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("UTXOs");
+        let update = doc! {"$pull": {"utxos": {"unspent_hash": &self.unspent_hash}}};
+        match collection
+            .update_one(doc! {"wallet": wallet.to_string()}, update)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Error during the spending of utxo-(tools/utxo 118)"),
+        }
+    }
+
     //insert outputs of a transactions as UTXO
     pub async fn generate<'a>(
         block: u64,
-        trx_hash: &String,
-        output_hash: &String,
+        trx_hash: &str,
+        output_hash: &str,
         unspent: &Unspent,
         db: &Database,
     ) -> Result<(), &'a str> {
-        let wallet = unspent.data.wallet;
         //make utxo with arguments for insert to database
-        let utxo = Self {
-            block: block,
-            trx_hash: trx_hash.to_string(),
-            output_hash: output_hash.to_string(),
-            unspent_hash: unspent.hash.to_string(),
-            unspent: unspent.data.value,
-        };
+        let utxo = Self::new(block, trx_hash, output_hash, unspent);
+        utxo.insert(&unspent.data.wallet, db).await
+    }
+
+    //add utxo to utxos of a wallet, it is used for new outputs and for giving back inputs of an evicted transaction
+    pub async fn insert<'a>(&self, wallet: &Public, db: &Database) -> Result<(), &'a str> {
         let collection: Collection<Document> = db.collection("UTXOs");
         let query = collection
             .find_one(doc! {"wallet": wallet.to_string()})
@@ -103,14 +143,22 @@ impl UTXO {
                 //if person was in the utxos database update it
                 //else make and insert new person
                 if let Some(doc) = opt {
-                    let utxo_to_doc = to_document(&utxo).unwrap();
+                    //a utxo is added once, so applying a block again doesn't duplicate its outputs
+                    match from_document::<Person>(doc.clone()) {
+                        Ok(person) if person.utxos.iter().any(|u| u.unspent_hash == self.unspent_hash) => {
+                            return Ok(())
+                        }
+                        Ok(_) => {}
+                        Err(_) => return Err("Error while reading utxos-(tools/utxo 152)"),
+                    }
+                    let utxo_to_doc = to_document(self).unwrap();
                     let update = doc! {"$push": {"utxos": utxo_to_doc}};
                     match collection.update_one(doc, update).await {
                         Ok(_) => Ok(()),
                         Err(_) => Err("Error while updating utxos-(tools/utxo 106)"),
                     }
                 } else {
-                    let new_person = Person::new(wallet, vec![utxo]);
+                    let new_person = Person::new(*wallet, vec![self.clone()]);
                     let person_to_doc = to_document(&new_person).unwrap();
                     match collection.insert_one(person_to_doc).await {
                         Ok(_) => Ok(()),
@@ -1,6 +1,6 @@
 use libp2p::{futures::StreamExt, PeerId};
 use mongodb::{
-    bson::{doc, from_document, to_document, Document},
+    bson::{doc, from_document, Document},
     Collection, Database,
 };
 
@@ -13,61 +13,58 @@ impl Waiting {
         db: &'a Database,
         block_generator: Option<&PeerId>,
     ) -> Result<(), &'a str> {
+        let waiting = Self::next(db, block_generator).await?;
+        Self::save(db, &waiting).await
+    }
+
+    //waiting of validators after a block, it is made before changing anything
+    //so saving it again after a stop in the middle of a block gives the same waiting
+    pub async fn next<'a>(
+        db: &'a Database,
+        block_generator: Option<&PeerId>,
+    ) -> Result<Vec<(PeerId, u64)>, &'a str> {
         let collection: Collection<Document> = db.collection("validators");
-        let query = collection.find(doc! {}).await;
-        match query {
+        let mut validators = Vec::new();
+        match collection.find(doc! {}).await {
             Ok(mut cursor) => {
-                let mut is_err = None;
                 while let Some(result) = cursor.next().await {
-                    match result {
-                        Ok(doc) => {
-                            let mut validator: Validator = from_document(doc.clone()).unwrap();
-                            //if validator was generator of block its waiting should be count of validators in the network
-                            //else its waiting should sets waiting - 1
-                            if block_generator.is_some()
-                                && &validator.peerid == block_generator.unwrap()
-                            {
-                                validator.waiting =
-                                    collection.count_documents(doc! {}).await.unwrap() as u64 - 1;
-                                let replacement = to_document(&validator).unwrap();
-                                match collection.replace_one(doc, replacement).await {
-                                    Ok(_) => {}
-                                    Err(_) => {
-                                        is_err.get_or_insert("Error during the replacing of document-(tools/waiting 36)");
-                                        break;
-                                    }
-                                }
-                            } else {
-                                //if waiting is greater than 0 then it should sets waiting -1
-                                if validator.waiting > 0 {
-                                    validator.waiting -= 1;
-                                    let replacement = to_document(&validator).unwrap();
-                                    match collection.replace_one(doc, replacement).await {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            is_err.get_or_insert("Error during the replacing of document-(tools/waiting 48)");
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            is_err.get_or_insert(
-                                "Error in err arm of check result of cursor-(tools/waiting 57)",
-                            );
-                            break;
+                    match result.map(from_document::<Validator>) {
+                        Ok(Ok(validator)) => validators.push(validator),
+                        _ => {
+                            return Err("Error in err arm of check result of cursor-(tools/waiting 34)")
                         }
                     }
                 }
-                if is_err.is_none() {
-                    Ok(())
-                } else {
-                    Err(is_err.unwrap())
-                }
             }
-            Err(_) => Err("Error during quering in-(tools/waiting 69)"),
+            Err(_) => return Err("Error during quering in-(tools/waiting 39)"),
+        }
+
+        //if validator was generator of block its waiting should be count of validators in the network
+        //else its waiting should sets waiting - 1
+        let count = validators.len() as u64;
+        Ok(validators
+            .into_iter()
+            .map(|validator| match block_generator {
+                Some(generator) if &validator.peerid == generator => (validator.peerid, count - 1),
+                _ => (validator.peerid, validator.waiting.saturating_sub(1)),
+            })
+            .collect())
+    }
+
+    //set waiting of validators
+    pub async fn save<'a>(db: &'a Database, waiting: &[(PeerId, u64)]) -> Result<(), &'a str> {
+        let collection: Collection<Document> = db.collection("validators");
+        for (peerid, waiting) in waiting {
+            let update = doc! {"$set": {"waiting": *waiting as i64}};
+            if collection
+                .update_one(doc! {"peerid": peerid.to_string()}, update)
+                .await
+                .is_err()
+            {
+                return Err("Error during the replacing of document-(tools/waiting 64)");
+            }
         }
+        Ok(())
     }
 
     //return new waiting as number for set it to new validator that gossips itself with vsync message
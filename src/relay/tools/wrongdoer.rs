@@ -1,9 +1,12 @@
 use libp2p::PeerId;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, to_document, Document},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::relay::practical::stake::Stake;
 
 use super::waiting::Waiting;
 
@@ -14,6 +17,26 @@ pub struct WrongDoer {
 }
 
 impl WrongDoer {
+    pub fn new(peerid: PeerId, cause: &str) -> Self {
+        Self {
+            peerid,
+            cause: cause.to_string(),
+        }
+    }
+
+    //slash stakes of a validator that its misbehaviour is proved and keep the cause of it
+    pub async fn slash<'a>(&self, db: &'a Database) -> Result<(), &'a str> {
+        Stake::slash(db, &self.peerid).await?;
+        let collection: Collection<Document> = db.collection("wrongdoers");
+        match collection.insert_one(to_document(self).unwrap()).await {
+            Ok(_) => {
+                warn!("Wrongdoer {} slashed: {}", self.peerid, self.cause);
+                Ok(())
+            }
+            Err(_) => Err("Inserting wrongdoer problem-(tools/wrongdoer 35)"),
+        }
+    }
+
     pub async fn remove<'a>(db: &'a Database, peerid: PeerId) -> Result<PeerId, &'a str> {
         let collection: Collection<Document> = db.collection("validators");
         let filter = doc! {"$or": [{"peerid": peerid.to_string()}, {"relay": peerid.to_string()}]}; // filter validators who their peerid is wrongdoer or its rela
@@ -4,26 +4,30 @@ use std::{
     path::Path,
     process::Command,
 };
+use tracing::info;
 
-use super::create_log::write_log;
+use crate::relay::practical::config::CONFIG;
 
 pub struct Zip;
 
 impl Zip {
     pub fn extract<'a>(to: &str) -> Result<(), &'a str> {
         fs::create_dir_all(to).unwrap();
-        match zip::ZipArchive::new(File::open("/home/Centichain.zip").unwrap()) {
+        match zip::ZipArchive::new(File::open(CONFIG.zip_path()).unwrap()) {
             Ok(mut archive) => {
                 for i in 0..archive.len() {
                     let mut item = archive.by_index(i).unwrap();
                     if item.is_file() {
-                        let mut output = File::create(item.name()).unwrap();
+                        //bson files are extracted by their names into the destination directory
+                        let file_name = Path::new(item.name()).file_name().unwrap().to_owned();
+                        let mut output = File::create(Path::new(to).join(file_name)).unwrap();
                         let mut bytes = Vec::new();
                         item.read_to_end(&mut bytes).unwrap();
                         output.write_all(&bytes).unwrap();
                     }
                 }
-                Ok(write_log("Zip File Of Blockchain Extracted"))
+                info!("Zip File Of Blockchain Extracted");
+                Ok(())
             }
             Err(_e) => Err("Extract Zip File Error-(event/syncing-26)"),
         }
@@ -31,8 +35,10 @@ impl Zip {
 
     pub fn maker<'a>() -> Result<(), &'a str> {
         //if zip file and dump folder existed removes those at first
-        let path1 = Path::new("/home/Centichain.zip");
-        let path2 = Path::new("/etc/dump");
+        let zip_path = CONFIG.zip_path();
+        let dump_dir = CONFIG.dump_dir();
+        let path1 = Path::new(&zip_path);
+        let path2 = Path::new(&dump_dir);
 
         //remove path 1
         if path1.exists() {
@@ -46,17 +52,19 @@ impl Zip {
 
         //after remove zip file, makes a new
         match Command::new("mongodump")
+            .arg("--uri")
+            .arg(&CONFIG.mongo_uri)
             .arg("--db")
             .arg("Centichain")
             .arg("--out")
-            .arg("/etc/dump")
+            .arg(&dump_dir)
             .output()
         {
             Ok(_) => {
                 match Command::new("zip")
                     .arg("-r")
-                    .arg("/home/Centichain.zip")
-                    .arg("/etc/dump/Centichain")
+                    .arg(&zip_path)
+                    .arg(format!("{}/Centichain", dump_dir))
                     .output()
                 {
                     Ok(_) => Ok(()),